@@ -0,0 +1,270 @@
+use crate::exit_code::ExitCode;
+use crate::rng::Rng;
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+
+/// Which exit codes a generated argv may legitimately produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expect {
+    /// Broken at the argument level: usage, invalid query or invalid budget.
+    Rejected,
+    /// Well-formed but unusual (e.g. a very long plain query): success is
+    /// also acceptable.
+    RejectedOrAccepted,
+    /// A `--cache` or `--sources` path that is not valid UTF-8. It is still a
+    /// valid OS path, so any documented outcome except an internal error is
+    /// acceptable.
+    NonUtf8Path,
+}
+
+impl Expect {
+    pub fn allows(self, code: ExitCode) -> bool {
+        let argument_error = matches!(
+            code,
+            ExitCode::Usage | ExitCode::InvalidQuery | ExitCode::InvalidBudget
+        );
+        match self {
+            Self::Rejected => argument_error,
+            Self::RejectedOrAccepted => argument_error || code == ExitCode::Success,
+            Self::NonUtf8Path => code != ExitCode::Internal,
+        }
+    }
+}
+
+/// One generated CLI invocation.
+#[derive(Debug, Clone)]
+pub struct ArgvCase {
+    /// Short description of the mutation, for failure messages.
+    pub mutation: String,
+    pub args: Vec<OsString>,
+    pub expect: Expect,
+}
+
+/// Real paths the generator builds otherwise-valid invocations from.
+#[derive(Debug, Clone)]
+pub struct ArgvPaths {
+    /// A valid cache.
+    pub cache: PathBuf,
+    /// A valid sources directory.
+    pub sources: PathBuf,
+    /// Where `build` may write; need not exist.
+    pub output: PathBuf,
+}
+
+/// Deterministic generator of malformed `context` argv variants.
+pub struct ArgvFuzzer {
+    rng: Rng,
+    paths: ArgvPaths,
+}
+
+/// `--budget` values no unsigned integer parser should accept.
+const BAD_BUDGETS: &[&str] = &[
+    "-1",
+    "1e3",
+    "0x10",
+    "18446744073709551616",
+    "99999999999999999999999999",
+    "",
+    " ",
+    "4000 ",
+    " 4000",
+    "+-1",
+    "4_000",
+    "4,000",
+    "4000.0",
+    "1.5",
+    "NaN",
+    "inf",
+    "-inf",
+    "four",
+    "\u{663}",
+    "\u{ff14}\u{ff10}\u{ff10}\u{ff10}",
+    "--",
+    "--budget",
+];
+
+const UNKNOWN_FLAGS: &[&str] = &[
+    "--bogus",
+    "-x",
+    "--CACHE",
+    "--cache-dir",
+    "--budgets",
+    "---cache",
+    "--\u{0441}ache",
+    "-",
+    "--=",
+];
+
+const CONTROL_QUERIES: &[&str] = &[
+    "\u{7}",
+    "deployment\u{7}security",
+    "\u{1b}[31mdeployment",
+    "deployment\rsecurity",
+    "\u{8}\u{8}",
+    "\u{7f}",
+    "deployment\u{85}",
+];
+
+#[derive(Clone, Copy)]
+enum Command {
+    Build,
+    Resolve,
+    Inspect,
+}
+
+impl ArgvFuzzer {
+    pub fn new(seed: u64, paths: ArgvPaths) -> Self {
+        Self {
+            rng: Rng::new(seed),
+            paths,
+        }
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.rng.below(n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+
+    /// A valid argv for `command` as `(flag, value)` pairs.
+    fn valid(&self, command: Command) -> Vec<(String, OsString)> {
+        let p = &self.paths;
+        match command {
+            Command::Build => vec![
+                ("--sources".into(), p.sources.clone().into()),
+                ("--cache".into(), p.output.clone().into()),
+            ],
+            Command::Resolve => vec![
+                ("--cache".into(), p.cache.clone().into()),
+                ("--query".into(), "deployment".into()),
+                ("--budget".into(), "4000".into()),
+            ],
+            Command::Inspect => vec![("--cache".into(), p.cache.clone().into())],
+        }
+    }
+
+    pub fn next_case(&mut self) -> ArgvCase {
+        let command = [Command::Build, Command::Resolve, Command::Inspect][self.below(3)];
+        let name = match command {
+            Command::Build => "build",
+            Command::Resolve => "resolve",
+            Command::Inspect => "inspect",
+        };
+        let mut pairs = self.valid(command);
+        self.rng.shuffle(&mut pairs);
+
+        let mut expect = Expect::Rejected;
+        let mut extra: Vec<OsString> = Vec::new();
+        let mutation = match self.below(if matches!(command, Command::Resolve) {
+            8
+        } else {
+            5
+        }) {
+            0 => {
+                let flag = self.pick(UNKNOWN_FLAGS);
+                let at = self.below(pairs.len() + 1);
+                pairs.insert(at, (flag.to_string(), "value".into()));
+                format!("unknown flag {flag:?}")
+            }
+            1 => {
+                let i = self.below(pairs.len());
+                let dup = pairs[i].clone();
+                let at = self.below(pairs.len() + 1);
+                pairs.insert(at, dup.clone());
+                format!("duplicated {}", dup.0)
+            }
+            2 => {
+                // The last flag loses its value.
+                let (flag, _) = pairs.pop().unwrap();
+                extra.push(flag.clone().into());
+                format!("missing value for {flag}")
+            }
+            3 => {
+                let i = self.below(pairs.len());
+                let (flag, _) = pairs.remove(i);
+                format!("missing {flag}")
+            }
+            4 => {
+                // Only paths may be non-UTF-8; a query or budget must be rejected.
+                let i = self.below(pairs.len());
+                pairs[i].1 = non_utf8(&pairs[i].1);
+                if pairs[i].0 == "--cache" || pairs[i].0 == "--sources" {
+                    expect = Expect::NonUtf8Path;
+                }
+                format!("non-UTF-8 {}", pairs[i].0)
+            }
+            5 => {
+                let value = self.pick(BAD_BUDGETS);
+                set(&mut pairs, "--budget", value.into());
+                format!("--budget {value:?}")
+            }
+            6 => {
+                let value = self.pick(CONTROL_QUERIES);
+                set(&mut pairs, "--query", value.into());
+                format!("control-character query {value:?}")
+            }
+            _ => {
+                // Linux caps a single argument at 128 KiB.
+                let len = [1 << 12, 1 << 16, 120_000][self.below(3)];
+                let word = self.pick(&["deployment ", "x", "\u{1f600}", "\u{0}"]);
+                if word == "\u{0}" {
+                    // NUL cannot appear in argv; a non-UTF-8 query stands in for it.
+                    set(&mut pairs, "--query", non_utf8_query());
+                    "non-UTF-8 query".to_string()
+                } else {
+                    set(&mut pairs, "--query", word.repeat(len / word.len()).into());
+                    expect = Expect::RejectedOrAccepted;
+                    format!("{len}-byte query of {word:?}")
+                }
+            }
+        };
+
+        let mut args: Vec<OsString> = vec![name.into()];
+        for (flag, value) in pairs {
+            args.push(flag.into());
+            args.push(value);
+        }
+        args.extend(extra);
+        ArgvCase {
+            mutation: format!("{name}: {mutation}"),
+            args,
+            expect,
+        }
+    }
+}
+
+fn set(pairs: &mut [(String, OsString)], flag: &str, value: OsString) {
+    for pair in pairs.iter_mut().filter(|(f, _)| f == flag) {
+        pair.1 = value.clone();
+    }
+}
+
+#[cfg(unix)]
+fn non_utf8(value: &OsStr) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    let mut bytes = value.to_os_string().into_vec();
+    bytes.extend_from_slice(b"\xff\xfe");
+    OsString::from_vec(bytes)
+}
+
+#[cfg(not(unix))]
+fn non_utf8(value: &OsStr) -> OsString {
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    let mut wide: Vec<u16> = value.encode_wide().collect();
+    wide.push(0xd800);
+    OsString::from_wide(&wide)
+}
+
+#[cfg(unix)]
+fn non_utf8_query() -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(b"deployment\xff\xfe".to_vec())
+}
+
+#[cfg(not(unix))]
+fn non_utf8_query() -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+    OsString::from_wide(&[0x64, 0xd800])
+}
@@ -0,0 +1,143 @@
+use crate::rusage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+/// Resource usage of one child process run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub wall: Duration,
+    pub user: Duration,
+    pub sys: Duration,
+    /// Peak resident set size in KiB.
+    pub max_rss_kb: u64,
+}
+
+/// Median resource usage over several runs of one benchmark case.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    pub wall_ms: f64,
+    /// User plus system CPU time.
+    pub cpu_ms: f64,
+    pub max_rss_kb: u64,
+}
+
+/// Allowed slowdown before a case counts as a regression.
+///
+/// A metric regresses when `current > reference * ratio + floor`; the floor
+/// keeps millisecond-scale noise on tiny fixtures from failing the run.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Tolerance {
+    pub time_ratio: f64,
+    pub time_floor_ms: f64,
+    pub rss_ratio: f64,
+    pub rss_floor_kb: u64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            time_ratio: 1.5,
+            time_floor_ms: 25.0,
+            rss_ratio: 1.3,
+            rss_floor_kb: 8 * 1024,
+        }
+    }
+}
+
+/// Committed reference numbers: `fixtures/v0/bench/baseline.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Baseline {
+    #[serde(default)]
+    pub tolerance: Tolerance,
+    /// Median stats keyed by case name, e.g. `resolve/realistic/basic`.
+    #[serde(default)]
+    pub cases: BTreeMap<String, Stats>,
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        serde_json::from_str(&content).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json + "\n").map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+/// Run `cmd` to completion with stdio discarded, measuring wall time and the
+/// child's own rusage (not the harness's).
+pub fn run(cmd: &mut Command) -> Result<(ExitStatus, Sample), std::io::Error> {
+    cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+    let started = Instant::now();
+    let mut child = cmd.spawn()?;
+    let (status, usage) = rusage::wait(&mut child)?;
+    Ok((
+        status,
+        Sample {
+            wall: started.elapsed(),
+            user: usage.user,
+            sys: usage.sys,
+            max_rss_kb: usage.max_rss_kb,
+        },
+    ))
+}
+
+/// Median of each metric over `samples`. Panics on an empty slice.
+pub fn summarize(samples: &[Sample]) -> Stats {
+    assert!(!samples.is_empty(), "no samples to summarize");
+    let median = |mut v: Vec<f64>| {
+        v.sort_by(|a, b| a.total_cmp(b));
+        v[v.len() / 2]
+    };
+    Stats {
+        wall_ms: median(
+            samples
+                .iter()
+                .map(|s| s.wall.as_secs_f64() * 1000.0)
+                .collect(),
+        ),
+        cpu_ms: median(
+            samples
+                .iter()
+                .map(|s| (s.user + s.sys).as_secs_f64() * 1000.0)
+                .collect(),
+        ),
+        max_rss_kb: median(samples.iter().map(|s| s.max_rss_kb as f64).collect()) as u64,
+    }
+}
+
+/// Describe every metric of `current` that regressed against `reference`.
+pub fn regressions(current: &Stats, reference: &Stats, tol: &Tolerance) -> Vec<String> {
+    let mut out = Vec::new();
+    let time_limit = |r: f64| r * tol.time_ratio + tol.time_floor_ms;
+    if current.wall_ms > time_limit(reference.wall_ms) {
+        out.push(format!(
+            "wall {:.1}ms > {:.1}ms (reference {:.1}ms)",
+            current.wall_ms,
+            time_limit(reference.wall_ms),
+            reference.wall_ms
+        ));
+    }
+    if current.cpu_ms > time_limit(reference.cpu_ms) {
+        out.push(format!(
+            "cpu {:.1}ms > {:.1}ms (reference {:.1}ms)",
+            current.cpu_ms,
+            time_limit(reference.cpu_ms),
+            reference.cpu_ms
+        ));
+    }
+    let rss_limit = (reference.max_rss_kb as f64 * tol.rss_ratio) as u64 + tol.rss_floor_kb;
+    if current.max_rss_kb > rss_limit {
+        out.push(format!(
+            "peak rss {}KiB > {}KiB (reference {}KiB)",
+            current.max_rss_kb, rss_limit, reference.max_rss_kb
+        ));
+    }
+    out
+}
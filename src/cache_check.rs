@@ -0,0 +1,100 @@
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Identity of a cache that passed [`check_integrity`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheSummary {
+    pub cache_version: String,
+    pub document_ids: Vec<String>,
+}
+
+/// Verify the on-disk structure of a built cache without going through the CLI.
+///
+/// Checks that `manifest.json`, `index.json` and every document file parse,
+/// agree with each other, and that `documents/` holds no unreferenced files.
+/// A cache mixing two builds, or one truncated mid-write, fails at least one check.
+pub fn check_integrity(cache: &Path) -> Result<CacheSummary, String> {
+    let manifest = read_json(&cache.join("manifest.json"))?;
+    let index = read_json(&cache.join("index.json"))?;
+
+    let cache_version = manifest["cache_version"]
+        .as_str()
+        .ok_or("manifest cache_version is not a string")?
+        .to_string();
+    let entries = manifest["documents"]
+        .as_array()
+        .ok_or("manifest documents is not an array")?;
+    if manifest["document_count"].as_u64() != Some(entries.len() as u64) {
+        return Err(format!(
+            "manifest document_count {} does not match {} entries",
+            manifest["document_count"],
+            entries.len()
+        ));
+    }
+
+    let index = index.as_object().ok_or("index.json is not an object")?;
+    if index.len() != entries.len() {
+        return Err(format!(
+            "index.json has {} entries, manifest has {}",
+            index.len(),
+            entries.len()
+        ));
+    }
+
+    let mut document_ids = Vec::with_capacity(entries.len());
+    let mut referenced = BTreeSet::new();
+    for entry in entries {
+        let id = entry["id"].as_str().ok_or("manifest entry without id")?;
+        let version = entry["version"]
+            .as_str()
+            .ok_or_else(|| format!("manifest entry {id} without version"))?;
+        let file = entry["file"]
+            .as_str()
+            .ok_or_else(|| format!("manifest entry {id} without file"))?;
+
+        if index.get(id).and_then(Value::as_str) != Some(file) {
+            return Err(format!("index.json entry for {id} does not point at {file}"));
+        }
+
+        // Document files are content-addressed, so entries with identical content
+        // share one file, which records the id of just one of them.
+        let doc = read_json(&cache.join(file))?;
+        let doc_id = doc["id"].as_str().unwrap_or_default();
+        let shares_file = |e: &Value| e["id"] == doc_id && e["file"] == file;
+        if doc["version"] != version || !(doc_id == id || entries.iter().any(shares_file)) {
+            return Err(format!(
+                "{file} holds {}@{}, manifest expects {id}@{version}",
+                doc["id"], doc["version"]
+            ));
+        }
+        if !doc["content"].is_string() {
+            return Err(format!("{file} has no string content"));
+        }
+
+        document_ids.push(id.to_string());
+        referenced.insert(Path::new(file).to_path_buf());
+    }
+
+    let docs_dir = cache.join("documents");
+    if docs_dir.is_dir() {
+        for entry in std::fs::read_dir(&docs_dir).map_err(|e| format!("{}: {e}", docs_dir.display()))? {
+            let entry = entry.map_err(|e| format!("{}: {e}", docs_dir.display()))?;
+            let rel = Path::new("documents").join(entry.file_name());
+            if !referenced.contains(&rel) {
+                return Err(format!("{} is not referenced by the manifest", rel.display()));
+            }
+        }
+    }
+
+    Ok(CacheSummary {
+        cache_version,
+        document_ids,
+    })
+}
+
+fn read_json(path: &Path) -> Result<Value, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    serde_json::from_str(&content).map_err(|e| format!("{}: {e}", path.display()))
+}
@@ -1,5 +1,12 @@
+use crate::exit_code::ExitCode;
+use crate::rusage::{self, ResourceUsage};
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Output};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 /// Runner that invokes the `context` CLI binary via `std::process::Command`.
 ///
@@ -8,19 +15,213 @@ pub struct CliRunner {
     bin: PathBuf,
 }
 
-/// Result of a CLI invocation containing stdout, stderr, and exit code.
+/// How a CLI process ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// Exited normally with this status code.
+    Exited(i32),
+    /// Killed by a signal (unix only).
+    Signaled { signal: i32, core_dumped: bool },
+}
+
+impl Termination {
+    fn from_status(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = status.signal() {
+                return Self::Signaled {
+                    signal,
+                    core_dumped: status.core_dumped(),
+                };
+            }
+        }
+        Self::Exited(status.code().unwrap_or(-1))
+    }
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Exited(code) => write!(f, "exit code {code}"),
+            Self::Signaled { signal, core_dumped } => {
+                write!(f, "killed by signal {signal}")?;
+                if let Some(name) = signal_name(signal) {
+                    write!(f, " ({name})")?;
+                }
+                if core_dumped {
+                    write!(f, ", core dumped")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn signal_name(signal: i32) -> Option<&'static str> {
+    #[cfg(unix)]
+    {
+        let name = match signal {
+            libc::SIGABRT => "SIGABRT",
+            libc::SIGBUS => "SIGBUS",
+            libc::SIGFPE => "SIGFPE",
+            libc::SIGILL => "SIGILL",
+            libc::SIGINT => "SIGINT",
+            libc::SIGKILL => "SIGKILL",
+            libc::SIGPIPE => "SIGPIPE",
+            libc::SIGSEGV => "SIGSEGV",
+            libc::SIGTERM => "SIGTERM",
+            _ => return None,
+        };
+        Some(name)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signal;
+        None
+    }
+}
+
+/// Result of a CLI invocation: captured output, how the process ended, and
+/// what it cost.
 pub struct CliOutput {
     pub stdout: String,
     pub stderr: String,
+    /// Exit status code, or -1 if the process was killed by a signal;
+    /// see `termination` for the distinction.
     pub exit_code: i32,
+    pub termination: Termination,
+    /// Wall-clock time from spawn to reap.
+    pub duration: Duration,
+    /// The child's own CPU time and peak RSS.
+    pub usage: ResourceUsage,
 }
 
 impl CliOutput {
-    fn from_output(output: Output) -> Self {
-        Self {
-            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
-            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
-            exit_code: output.status.code().unwrap_or(-1),
+    /// The documented exit code, or `None` after a signal death or an
+    /// exit status outside the frozen contract.
+    pub fn exit(&self) -> Option<ExitCode> {
+        match self.termination {
+            Termination::Exited(code) => ExitCode::from_code(code),
+            Termination::Signaled { .. } => None,
+        }
+    }
+
+    /// One-line account of how the process ended, its cost, and its stderr,
+    /// for assertion messages.
+    pub fn describe(&self) -> String {
+        format!(
+            "{} after {:.1}ms (user {:.1}ms, sys {:.1}ms, peak rss {}KiB); stderr: {}",
+            self.termination,
+            self.duration.as_secs_f64() * 1000.0,
+            self.usage.user.as_secs_f64() * 1000.0,
+            self.usage.sys.as_secs_f64() * 1000.0,
+            self.usage.max_rss_kb,
+            self.stderr.trim_end()
+        )
+    }
+}
+
+/// A spawned CLI process whose stdout and stderr are drained on background
+/// threads so a chatty child can never block on a full pipe.
+struct Running {
+    child: Child,
+    started: Instant,
+    stdout: JoinHandle<Vec<u8>>,
+    stderr: JoinHandle<Vec<u8>>,
+}
+
+impl Running {
+    fn spawn(cmd: &mut Command) -> Result<Self, std::io::Error> {
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let started = Instant::now();
+        let mut child = cmd.spawn()?;
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+        Ok(Self {
+            child,
+            started,
+            stdout,
+            stderr,
+        })
+    }
+
+    fn wait(mut self) -> Result<CliOutput, std::io::Error> {
+        let (status, usage) = rusage::wait(&mut self.child)?;
+        let duration = self.started.elapsed();
+        let stdout = self.stdout.join().unwrap_or_default();
+        let stderr = self.stderr.join().unwrap_or_default();
+        Ok(CliOutput {
+            stdout: String::from_utf8_lossy(&stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&stderr).into_owned(),
+            exit_code: status.code().unwrap_or(-1),
+            termination: Termination::from_status(status),
+            duration,
+            usage,
+        })
+    }
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+fn run(cmd: &mut Command) -> Result<CliOutput, std::io::Error> {
+    Running::spawn(cmd)?.wait()
+}
+
+/// Result of [`CliRunner::build_and_kill`].
+pub enum KillOutcome {
+    /// The process exited, or crashed on its own, before the kill point.
+    Finished(CliOutput),
+    /// The process was still running and was killed (SIGKILL on unix).
+    Killed,
+}
+
+/// Process environment applied to a CLI invocation.
+///
+/// The default leaves the inherited environment, working directory and umask untouched.
+#[derive(Debug, Clone, Default)]
+pub struct CliEnv {
+    /// Start from an empty environment (`Command::env_clear`) before applying `vars`.
+    pub clear: bool,
+    /// Environment variables to set, applied in order.
+    pub vars: Vec<(String, String)>,
+    /// Working directory for the child process.
+    pub current_dir: Option<PathBuf>,
+    /// File mode creation mask for the child process (unix only).
+    pub umask: Option<u32>,
+}
+
+impl CliEnv {
+    fn apply(&self, cmd: &mut Command) {
+        if self.clear {
+            cmd.env_clear();
+        }
+        for (key, value) in &self.vars {
+            cmd.env(key, value);
+        }
+        if let Some(dir) = &self.current_dir {
+            cmd.current_dir(dir);
+        }
+        #[cfg(unix)]
+        if let Some(mask) = self.umask {
+            use std::os::unix::process::CommandExt;
+            // SAFETY: umask(2) is async-signal-safe and touches no Rust state.
+            unsafe {
+                cmd.pre_exec(move || {
+                    libc::umask(mask as libc::mode_t);
+                    Ok(())
+                });
+            }
         }
     }
 }
@@ -36,7 +237,34 @@ impl CliRunner {
     pub fn from_env() -> Option<Self> {
         std::env::var("CONTEXT_CLI_BIN")
             .ok()
-            .map(|p| Self::new(p))
+            .map(Self::new)
+    }
+
+    /// A `Command` for the CLI binary with no arguments yet, for callers that
+    /// need control over stdio or process lifetime.
+    pub fn command(&self) -> Command {
+        Command::new(&self.bin)
+    }
+
+    /// Run the CLI with arbitrary arguments.
+    pub fn run<I, S>(&self, args: I) -> Result<CliOutput, std::io::Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.run_in(&CliEnv::default(), args)
+    }
+
+    /// Run the CLI with arbitrary arguments under a modified process environment.
+    pub fn run_in<I, S>(&self, env: &CliEnv, args: I) -> Result<CliOutput, std::io::Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut cmd = Command::new(&self.bin);
+        cmd.args(args);
+        env.apply(&mut cmd);
+        run(&mut cmd)
     }
 
     /// Run `context build --sources <sources> --cache <cache> [--force]`.
@@ -45,6 +273,19 @@ impl CliRunner {
         sources: &Path,
         cache: &Path,
         force: bool,
+    ) -> Result<CliOutput, std::io::Error> {
+        self.build_in(&CliEnv::default(), sources, cache, force)
+    }
+
+    /// Run `context build` under a modified process environment.
+    ///
+    /// Relative paths are resolved by the child against `env.current_dir`.
+    pub fn build_in(
+        &self,
+        env: &CliEnv,
+        sources: &Path,
+        cache: &Path,
+        force: bool,
     ) -> Result<CliOutput, std::io::Error> {
         let mut cmd = Command::new(&self.bin);
         cmd.arg("build")
@@ -55,7 +296,45 @@ impl CliRunner {
         if force {
             cmd.arg("--force");
         }
-        cmd.output().map(CliOutput::from_output)
+        env.apply(&mut cmd);
+        run(&mut cmd)
+    }
+
+    /// Spawn `context build` and kill it once `after` has elapsed.
+    ///
+    /// Used to leave a cache in whatever state the build reached at an
+    /// arbitrary point in its lifetime.
+    pub fn build_and_kill(
+        &self,
+        sources: &Path,
+        cache: &Path,
+        force: bool,
+        after: Duration,
+    ) -> Result<KillOutcome, std::io::Error> {
+        let mut cmd = Command::new(&self.bin);
+        cmd.arg("build")
+            .arg("--sources")
+            .arg(sources)
+            .arg("--cache")
+            .arg(cache);
+        if force {
+            cmd.arg("--force");
+        }
+
+        let mut running = Running::spawn(&mut cmd)?;
+        std::thread::sleep(after);
+        // Still unreaped, so this is a no-op if the build already exited.
+        running.child.kill()?;
+        let output = running.wait()?;
+        match output.termination {
+            #[cfg(unix)]
+            Termination::Signaled {
+                signal: libc::SIGKILL,
+                ..
+            } => Ok(KillOutcome::Killed),
+            // Exited, or died of another signal, before the kill point.
+            _ => Ok(KillOutcome::Finished(output)),
+        }
     }
 
     /// Run `context resolve --cache <cache> --query <query> --budget <budget>`.
@@ -66,26 +345,37 @@ impl CliRunner {
         query: &str,
         budget: usize,
     ) -> Result<CliOutput, std::io::Error> {
-        Command::new(&self.bin)
-            .arg("resolve")
+        self.resolve_in(&CliEnv::default(), cache, query, budget)
+    }
+
+    /// Run `context resolve` under a modified process environment.
+    ///
+    /// A relative `cache` is resolved by the child against `env.current_dir`.
+    pub fn resolve_in(
+        &self,
+        env: &CliEnv,
+        cache: &Path,
+        query: &str,
+        budget: usize,
+    ) -> Result<CliOutput, std::io::Error> {
+        let mut cmd = Command::new(&self.bin);
+        cmd.arg("resolve")
             .arg("--cache")
             .arg(cache)
             .arg("--query")
             .arg(query)
             .arg("--budget")
-            .arg(budget.to_string())
-            .output()
-            .map(CliOutput::from_output)
+            .arg(budget.to_string());
+        env.apply(&mut cmd);
+        run(&mut cmd)
     }
 
     /// Run `context inspect --cache <cache>`.
     /// Returns the raw CLI output.
     pub fn inspect(&self, cache: &Path) -> Result<CliOutput, std::io::Error> {
-        Command::new(&self.bin)
+        run(Command::new(&self.bin)
             .arg("inspect")
             .arg("--cache")
-            .arg(cache)
-            .output()
-            .map(CliOutput::from_output)
+            .arg(cache))
     }
 }
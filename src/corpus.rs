@@ -0,0 +1,166 @@
+use crate::rng::Rng;
+use std::path::Path;
+
+/// Parameters for a generated corpus. Equal specs always produce byte-identical corpora.
+#[derive(Debug, Clone)]
+pub struct CorpusSpec {
+    pub seed: u64,
+    /// Number of documents to generate.
+    pub documents: usize,
+    /// Number of distinct words in the vocabulary.
+    pub vocabulary: usize,
+    /// Zipf exponent for word frequencies; ~1.0 matches natural language.
+    pub zipf_exponent: f64,
+    /// Median document length in words (log-normal distribution).
+    pub median_words: f64,
+    /// Spread of the log-normal length distribution.
+    pub length_sigma: f64,
+    /// Upper bound on document length in words.
+    pub max_words: usize,
+    /// Fraction of documents that are empty.
+    pub empty_fraction: f64,
+}
+
+impl CorpusSpec {
+    /// A corpus of `documents` documents with production-like defaults.
+    pub fn new(seed: u64, documents: usize) -> Self {
+        Self {
+            seed,
+            documents,
+            vocabulary: 5_000,
+            zipf_exponent: 1.07,
+            median_words: 120.0,
+            length_sigma: 0.9,
+            max_words: 4_000,
+            empty_fraction: 0.01,
+        }
+    }
+}
+
+/// Words placed at the head of the Zipf distribution so fixture-style queries match.
+const HEAD_WORDS: &[&str] = &[
+    "the", "deployment", "security", "guide", "cache", "context", "rollback", "token", "audit",
+    "release", "config", "service",
+];
+
+const SYLLABLES: &[&str] = &[
+    "ka", "lo", "mi", "ne", "ru", "sa", "ti", "vo", "ze", "pa", "qui", "dor", "len", "mar", "ost",
+    "bel",
+];
+
+/// Deterministic generator of documents following a [`CorpusSpec`].
+///
+/// Word frequencies follow a Zipf distribution over a synthetic vocabulary and
+/// document lengths follow a log-normal distribution, which together resemble
+/// real documentation corpora far better than uniform random text.
+pub struct Corpus {
+    spec: CorpusSpec,
+    vocabulary: Vec<String>,
+    cdf: Vec<f64>,
+}
+
+impl Corpus {
+    pub fn new(spec: CorpusSpec) -> Self {
+        let vocabulary = build_vocabulary(spec.vocabulary);
+        let mut cdf = Vec::with_capacity(vocabulary.len());
+        let mut total = 0.0;
+        for rank in 1..=vocabulary.len() {
+            total += 1.0 / (rank as f64).powf(spec.zipf_exponent);
+            cdf.push(total);
+        }
+        for c in &mut cdf {
+            *c /= total;
+        }
+        Self {
+            spec,
+            vocabulary,
+            cdf,
+        }
+    }
+
+    pub fn spec(&self) -> &CorpusSpec {
+        &self.spec
+    }
+
+    /// The vocabulary, most frequent word first.
+    pub fn vocabulary(&self) -> &[String] {
+        &self.vocabulary
+    }
+
+    /// Relative path of the n-th document. Documents are spread over
+    /// subdirectories of 1000 so no single directory gets huge.
+    pub fn document_id(n: usize) -> String {
+        format!("d{:03}/doc{n:06}.md", n / 1000)
+    }
+
+    /// Generate every document as `(id, content)`, in id order.
+    pub fn documents(&self) -> impl Iterator<Item = (String, String)> + '_ {
+        let mut rng = Rng::new(self.spec.seed);
+        (0..self.spec.documents).map(move |n| (Self::document_id(n), self.content(&mut rng)))
+    }
+
+    /// Write every document under `dest`.
+    pub fn write(&self, dest: &Path) -> Result<(), std::io::Error> {
+        for (id, content) in self.documents() {
+            let path = dest.join(&id);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, content)?;
+        }
+        Ok(())
+    }
+
+    fn content(&self, rng: &mut Rng) -> String {
+        if rng.next_f64() < self.spec.empty_fraction {
+            return String::new();
+        }
+        let words = self.length(rng);
+        let mut out = String::new();
+        for i in 0..words {
+            if i > 0 {
+                // Roughly one line break per sentence-length run of words.
+                out.push(if rng.below(14) == 0 { '\n' } else { ' ' });
+            }
+            out.push_str(self.word(rng));
+        }
+        out
+    }
+
+    fn length(&self, rng: &mut Rng) -> usize {
+        // Box-Muller transform for a standard normal sample.
+        let u1 = rng.next_f64().max(f64::MIN_POSITIVE);
+        let u2 = rng.next_f64();
+        let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+        let len = (self.spec.median_words.ln() + self.spec.length_sigma * z).exp();
+        (len.round() as usize).clamp(1, self.spec.max_words)
+    }
+
+    fn word(&self, rng: &mut Rng) -> &str {
+        let u = rng.next_f64();
+        let rank = self.cdf.partition_point(|&c| c < u).min(self.vocabulary.len() - 1);
+        &self.vocabulary[rank]
+    }
+}
+
+fn build_vocabulary(size: usize) -> Vec<String> {
+    let mut words: Vec<String> = HEAD_WORDS.iter().take(size).map(|w| w.to_string()).collect();
+    let mut n = 0usize;
+    while words.len() < size {
+        // Base-16 digits over the syllable table give unique, pronounceable words.
+        let mut word = String::new();
+        let mut k = n;
+        loop {
+            word.push_str(SYLLABLES[k % SYLLABLES.len()]);
+            k /= SYLLABLES.len();
+            if k == 0 {
+                break;
+            }
+        }
+        if !HEAD_WORDS.contains(&word.as_str()) {
+            words.push(word);
+        }
+        n += 1;
+    }
+    words
+}
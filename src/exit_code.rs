@@ -0,0 +1,85 @@
+use std::fmt;
+
+/// Frozen `context` CLI exit codes (v0 contract, see the README table).
+///
+/// Each failure code except `Usage` has an MCP counterpart: the server
+/// reports the same condition as a tool error whose `error.code` is
+/// [`ExitCode::mcp_code`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ExitCode {
+    Success = 0,
+    Usage = 1,
+    InvalidQuery = 2,
+    InvalidBudget = 3,
+    CacheMissing = 4,
+    CacheInvalid = 5,
+    IoError = 6,
+    Internal = 7,
+}
+
+impl ExitCode {
+    /// Every documented code, in numeric order.
+    pub const ALL: [ExitCode; 8] = [
+        Self::Success,
+        Self::Usage,
+        Self::InvalidQuery,
+        Self::InvalidBudget,
+        Self::CacheMissing,
+        Self::CacheInvalid,
+        Self::IoError,
+        Self::Internal,
+    ];
+
+    /// The numeric process exit status.
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// The documented code for a process exit status, or `None` if undocumented.
+    pub fn from_code(code: i32) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.code() == code)
+    }
+
+    /// The MCP `error.code` string for the same condition.
+    ///
+    /// `None` for `Success` and for `Usage`, which has no MCP equivalent:
+    /// malformed tool arguments are rejected as JSON-RPC errors instead.
+    pub fn mcp_code(self) -> Option<&'static str> {
+        match self {
+            Self::Success | Self::Usage => None,
+            Self::InvalidQuery => Some("invalid_query"),
+            Self::InvalidBudget => Some("invalid_budget"),
+            Self::CacheMissing => Some("cache_missing"),
+            Self::CacheInvalid => Some("cache_invalid"),
+            Self::IoError => Some("io_error"),
+            Self::Internal => Some("internal_error"),
+        }
+    }
+
+    /// The `error.code` the CLI prints on stderr for this exit code
+    /// (`schemas/cli_error.schema.json`).
+    ///
+    /// Same as [`ExitCode::mcp_code`], plus `usage_error` for `Usage`.
+    pub fn cli_error_code(self) -> Option<&'static str> {
+        match self {
+            Self::Usage => Some("usage_error"),
+            other => other.mcp_code(),
+        }
+    }
+
+    /// The exit code for an MCP `error.code` string, or `None` if not frozen.
+    pub fn from_mcp_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.mcp_code() == Some(code))
+    }
+
+    /// All frozen MCP `error.code` strings, in exit-code order.
+    pub fn mcp_codes() -> impl Iterator<Item = &'static str> {
+        Self::ALL.into_iter().filter_map(Self::mcp_code)
+    }
+}
+
+impl fmt::Display for ExitCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:?})", self.code(), self)
+    }
+}
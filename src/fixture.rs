@@ -23,6 +23,25 @@ pub fn cache_path(name: &str) -> PathBuf {
     v0_root().join("caches").join(name)
 }
 
+/// Panic unless `path` has been recorded, naming the `make` target that
+/// records it. Suites call this once the binary the fixture comes from is
+/// available, so a missing fixture fails instead of skipping.
+#[track_caller]
+pub fn require_recorded(path: &Path, target: &str) {
+    assert!(
+        path.exists(),
+        "{} not recorded, run `make {target}` against the real binaries",
+        path.display()
+    );
+}
+
+/// [`cache_path`], panicking via [`require_recorded`] if the cache is missing.
+#[track_caller]
+pub fn require_cache(name: &str) -> PathBuf {
+    require_recorded(&cache_path(name).join("manifest.json"), "fixtures");
+    cache_path(name)
+}
+
 /// Path to a document fixture directory: `fixtures/v0/documents/{name}`.
 pub fn documents_path(name: &str) -> PathBuf {
     v0_root().join("documents").join(name)
@@ -46,6 +65,13 @@ pub fn expected(name: &str) -> String {
     canonicalize(&content)
 }
 
+/// [`expected`], panicking via [`require_recorded`] if the golden is missing.
+#[track_caller]
+pub fn require_expected(name: &str) -> String {
+    require_recorded(&v0_root().join("expected").join(format!("{name}.json")), "fixtures");
+    expected(name)
+}
+
 /// Root directory for schemas: `CARGO_MANIFEST_DIR/schemas`.
 pub fn schemas_root() -> PathBuf {
     Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas")
@@ -60,6 +86,32 @@ pub fn schema(name: &str) -> serde_json::Value {
         .unwrap_or_else(|e| panic!("failed to parse schema {}: {e}", path.display()))
 }
 
+/// Contract versions under `fixtures/`, as `N` for each `fixtures/vN`, ascending.
+pub fn contract_versions() -> Vec<u32> {
+    let mut versions: Vec<u32> = std::fs::read_dir(fixtures_root())
+        .map(|rd| {
+            rd.flatten()
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| e.file_name().to_str()?.strip_prefix('v')?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    versions.sort_unstable();
+    versions
+}
+
+/// Schemas of contract version `vN`: `fixtures/vN/schemas` if present.
+/// Otherwise v0 uses the top-level `schemas/` and later versions inherit
+/// the previous version's schemas unchanged.
+pub fn version_schemas_root(version: u32) -> PathBuf {
+    let own = fixtures_root().join(format!("v{version}")).join("schemas");
+    match version {
+        _ if own.is_dir() => own,
+        0 => schemas_root(),
+        n => version_schemas_root(n - 1),
+    }
+}
+
 /// Canonicalize output for cross-platform comparison.
 ///
 /// - Normalizes CRLF → LF
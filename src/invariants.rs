@@ -0,0 +1,76 @@
+use serde_json::Value;
+
+/// Check the structural invariants every `resolve` result must satisfy,
+/// independent of the documents or query that produced it:
+///
+/// - `tokens_used <= budget` and equals the sum of document `tokens`
+/// - `documents_selected == documents.len()`
+/// - `documents_considered == documents_selected + documents_excluded_by_budget`
+/// - every score is within `[0, 1]`
+/// - documents are ordered by `(score DESC, id ASC)`
+pub fn check_selection(result: &Value) -> Result<(), String> {
+    let docs = result["documents"]
+        .as_array()
+        .ok_or("documents is not an array")?;
+    let sel = &result["selection"];
+    let field = |name: &str| {
+        sel[name]
+            .as_u64()
+            .ok_or_else(|| format!("selection.{name} is not an unsigned integer"))
+    };
+    let budget = field("budget")?;
+    let tokens_used = field("tokens_used")?;
+    let considered = field("documents_considered")?;
+    let selected = field("documents_selected")?;
+    let excluded = field("documents_excluded_by_budget")?;
+
+    if tokens_used > budget {
+        return Err(format!("tokens_used {tokens_used} exceeds budget {budget}"));
+    }
+
+    let mut token_sum = 0u64;
+    for doc in docs {
+        token_sum += doc["tokens"]
+            .as_u64()
+            .ok_or_else(|| format!("document {} has no integer tokens", doc["id"]))?;
+    }
+    if token_sum != tokens_used {
+        return Err(format!(
+            "tokens_used {tokens_used} does not equal the sum of document tokens {token_sum}"
+        ));
+    }
+
+    if selected != docs.len() as u64 {
+        return Err(format!(
+            "documents_selected {selected} does not equal documents.len() {}",
+            docs.len()
+        ));
+    }
+    if considered != selected + excluded {
+        return Err(format!(
+            "documents_considered {considered} != selected {selected} + excluded {excluded}"
+        ));
+    }
+
+    let mut prev: Option<(f64, &str)> = None;
+    for doc in docs {
+        let id = doc["id"].as_str().ok_or("document without string id")?;
+        let score = doc["score"]
+            .as_f64()
+            .ok_or_else(|| format!("document {id} has no numeric score"))?;
+        if !(0.0..=1.0).contains(&score) {
+            return Err(format!("document {id} score {score} is outside [0, 1]"));
+        }
+        if let Some((prev_score, prev_id)) = prev {
+            let ordered = score < prev_score || (score == prev_score && id > prev_id);
+            if !ordered {
+                return Err(format!(
+                    "document {id} ({score}) is out of (score DESC, id ASC) order after {prev_id} ({prev_score})"
+                ));
+            }
+        }
+        prev = Some((score, id));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,400 @@
+use crate::rng::Rng;
+
+/// One generated JSON-RPC message: raw bytes for a single stdin line, which
+/// need not be valid JSON or UTF-8, and the generator that produced them.
+#[derive(Debug, Clone)]
+pub struct FuzzCase {
+    pub kind: &'static str,
+    pub bytes: Vec<u8>,
+}
+
+/// Deterministic grammar-based generator of hostile JSON-RPC messages.
+///
+/// The same seed always yields the same sequence of cases. No case contains
+/// a raw `\n`, so each is exactly one line on the wire.
+pub struct JsonRpcFuzzer {
+    rng: Rng,
+}
+
+const METHODS: &[&str] = &[
+    "initialize",
+    "ping",
+    "tools/list",
+    "tools/call",
+    "resources/list",
+    "prompts/list",
+    "notifications/initialized",
+    "notifications/cancelled",
+    "logging/setLevel",
+    "",
+    "tools/",
+    "TOOLS/LIST",
+    "tools/call\u{0}",
+];
+
+const TOOLS: &[&str] = &[
+    "context.resolve",
+    "context.inspect_cache",
+    "context.list_caches",
+    "context.unknown",
+    "",
+];
+
+const KINDS: &[&str] = &[
+    "envelope",
+    "deep_nesting",
+    "huge_string",
+    "invalid_utf8",
+    "lone_surrogate",
+    "odd_id",
+    "big_number",
+    "duplicate_keys",
+    "wrong_types",
+    "mutated",
+];
+
+impl JsonRpcFuzzer {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: Rng::new(seed) }
+    }
+
+    /// Every case kind, for reporting and fixture naming.
+    pub fn kinds() -> &'static [&'static str] {
+        KINDS
+    }
+
+    pub fn next_case(&mut self) -> FuzzCase {
+        let kind = KINDS[self.below(KINDS.len())];
+        let bytes = match kind {
+            "envelope" => self.envelope().into_bytes(),
+            "deep_nesting" => self.deep_nesting().into_bytes(),
+            "huge_string" => self.huge_string().into_bytes(),
+            "invalid_utf8" => self.invalid_utf8(),
+            "lone_surrogate" => self.lone_surrogate().into_bytes(),
+            "odd_id" => self.odd_id().into_bytes(),
+            "big_number" => self.big_number().into_bytes(),
+            "duplicate_keys" => self.duplicate_keys().into_bytes(),
+            "wrong_types" => self.wrong_types().into_bytes(),
+            "mutated" => self.mutated(),
+            _ => unreachable!(),
+        };
+        debug_assert!(!bytes.contains(&b'\n'));
+        FuzzCase { kind, bytes }
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.rng.below(n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+
+    fn chance(&mut self, p: f64) -> bool {
+        self.rng.next_f64() < p
+    }
+
+    /// A JSON string literal (quoted, escaped) for `s`.
+    fn quote(s: &str) -> String {
+        serde_json::to_string(s).unwrap()
+    }
+
+    /// A random JSON value of bounded depth.
+    fn value(&mut self, depth: usize) -> String {
+        let choice = if depth == 0 { self.below(4) } else { self.below(6) };
+        match choice {
+            0 => "null".to_string(),
+            1 => (if self.chance(0.5) { "true" } else { "false" }).to_string(),
+            2 => self.number(),
+            3 => self.string(),
+            4 => {
+                let n = self.below(4);
+                let items: Vec<String> = (0..n).map(|_| self.value(depth - 1)).collect();
+                format!("[{}]", items.join(","))
+            }
+            _ => {
+                let n = self.below(4);
+                let fields: Vec<String> = (0..n)
+                    .map(|_| {
+                        let key = self.string();
+                        format!("{key}:{}", self.value(depth - 1))
+                    })
+                    .collect();
+                format!("{{{}}}", fields.join(","))
+            }
+        }
+    }
+
+    fn number(&mut self) -> String {
+        let edge = [
+            "0",
+            "-0",
+            "1",
+            "-1",
+            "4000",
+            "9007199254740993",
+            "18446744073709551615",
+            "18446744073709551616",
+            "-9223372036854775809",
+            "1e400",
+            "-1e400",
+            "1e-400",
+            "1.5",
+            "123456789012345678901234567890",
+        ];
+        if self.chance(0.7) {
+            self.pick(&edge).to_string()
+        } else {
+            (self.rng.next_u64() as i64).to_string()
+        }
+    }
+
+    fn string(&mut self) -> String {
+        let samples = [
+            "",
+            "deployment",
+            "minimal",
+            "../",
+            "\u{0}",
+            "\u{202e}",
+            "\u{1f600}",
+            "\\",
+            "\"",
+            "caf\u{e9}",
+        ];
+        let s = self.pick(&samples).to_string();
+        Self::quote(&s)
+    }
+
+    fn params(&mut self, method: &str) -> String {
+        match method {
+            "tools/call" => {
+                let tool = self.pick(TOOLS);
+                let args = if self.chance(0.7) {
+                    let cache = self.pick(&["minimal", "realistic", "missing", "..", ""]);
+                    format!(
+                        r#"{{"cache":{},"query":{},"budget":{}}}"#,
+                        Self::quote(cache),
+                        self.string(),
+                        self.number()
+                    )
+                } else {
+                    self.value(3)
+                };
+                format!(r#"{{"name":{},"arguments":{args}}}"#, Self::quote(tool))
+            }
+            "initialize" => format!(
+                r#"{{"protocolVersion":{},"capabilities":{},"clientInfo":{}}}"#,
+                Self::quote(self.pick(&["2024-11-05", "1999-01-01", "", "9999-99-99"])),
+                self.value(2),
+                self.value(2)
+            ),
+            _ => self.value(2),
+        }
+    }
+
+    /// A plausible request or notification with random method and params.
+    fn envelope(&mut self) -> String {
+        let method = self.pick(METHODS);
+        let mut fields = vec![r#""jsonrpc":"2.0""#.to_string()];
+        if self.chance(0.8) {
+            fields.push(format!(r#""id":{}"#, self.below(1_000_000) + 1_000_000));
+        }
+        fields.push(format!(r#""method":{}"#, Self::quote(method)));
+        if self.chance(0.9) {
+            fields.push(format!(r#""params":{}"#, self.params(method)));
+        }
+        self.rng.shuffle(&mut fields);
+        format!("{{{}}}", fields.join(","))
+    }
+
+    fn deep_nesting(&mut self) -> String {
+        let depth = [64, 129, 1_000, 10_000, 100_000][self.below(5)];
+        let (open, close) = if self.chance(0.5) {
+            ("[".repeat(depth), "]".repeat(depth))
+        } else {
+            (r#"{"a":"#.repeat(depth), "}".repeat(depth))
+        };
+        let inner = format!("{open}1{close}");
+        match self.below(3) {
+            0 => inner,
+            1 => format!(r#"{{"jsonrpc":"2.0","id":7,"method":"tools/list","params":{inner}}}"#),
+            _ => format!(
+                r#"{{"jsonrpc":"2.0","id":7,"method":"tools/call","params":{{"name":"context.resolve","arguments":{{"cache":"minimal","query":"x","budget":{inner}}}}}}}"#
+            ),
+        }
+    }
+
+    fn huge_string(&mut self) -> String {
+        let len = [1 << 16, 1 << 20, 3 << 20, 8 << 20][self.below(4)];
+        let fill = self.pick(&["a", "\\u0000", "\\\\", "\u{1f600}", "deployment "]);
+        let body = fill.repeat(len / fill.len());
+        match self.below(3) {
+            0 => format!(
+                r#"{{"jsonrpc":"2.0","id":8,"method":"tools/call","params":{{"name":"context.resolve","arguments":{{"cache":"minimal","query":"{body}","budget":4000}}}}}}"#
+            ),
+            1 => format!(r#"{{"jsonrpc":"2.0","id":8,"method":"{body}"}}"#),
+            _ => format!(
+                r#"{{"jsonrpc":"2.0","id":8,"method":"tools/call","params":{{"name":"context.inspect_cache","arguments":{{"cache":"{body}"}}}}}}"#
+            ),
+        }
+    }
+
+    fn invalid_utf8(&mut self) -> Vec<u8> {
+        let bad: &[&[u8]] = &[
+            b"\xff",
+            b"\xfe\xff",
+            b"\xc0\xaf",
+            b"\xe2\x82",
+            b"\xed\xa0\x80",
+            b"\xf4\x90\x80\x80",
+            b"\x80\x80\x80",
+        ];
+        let bad = bad[self.below(bad.len())];
+        let template = self.envelope().into_bytes();
+        let mut out = Vec::with_capacity(template.len() + bad.len());
+        if self.chance(0.5) {
+            // Inside a string value, where a decoder is most likely to trust it.
+            let quote = template.iter().rposition(|&b| b == b'"').unwrap_or(0);
+            out.extend_from_slice(&template[..quote]);
+            out.extend_from_slice(bad);
+            out.extend_from_slice(&template[quote..]);
+        } else {
+            let at = self.below(template.len() + 1);
+            out.extend_from_slice(&template[..at]);
+            out.extend_from_slice(bad);
+            out.extend_from_slice(&template[at..]);
+        }
+        out
+    }
+
+    fn lone_surrogate(&mut self) -> String {
+        let s = self.pick(&[
+            "\\ud800",
+            "\\udfff",
+            "\\udc00\\ud800",
+            "\\ud800\\u0041",
+            "\\ud83d",
+            "x\\ud800y",
+        ]);
+        match self.below(3) {
+            0 => format!(r#"{{"jsonrpc":"2.0","id":9,"method":"{s}"}}"#),
+            1 => format!(
+                r#"{{"jsonrpc":"2.0","id":9,"method":"tools/call","params":{{"name":"context.resolve","arguments":{{"cache":"minimal","query":"{s}","budget":4000}}}}}}"#
+            ),
+            _ => format!(r#"{{"jsonrpc":"2.0","id":"{s}","method":"ping"}}"#),
+        }
+    }
+
+    fn odd_id(&mut self) -> String {
+        let id = self.pick(&[
+            "0",
+            "-1",
+            "-9223372036854775808",
+            "9223372036854775807",
+            "18446744073709551615",
+            "18446744073709551616",
+            "1e308",
+            "1e400",
+            "1.5",
+            "-0",
+            "\"\"",
+            "\"1\"",
+            "null",
+            "true",
+            "[]",
+            "{}",
+            "[1,2]",
+            "{\"id\":1}",
+        ]);
+        let method = self.pick(&["ping", "tools/list", "unknown/method"]);
+        format!(r#"{{"jsonrpc":"2.0","id":{id},"method":"{method}"}}"#)
+    }
+
+    fn big_number(&mut self) -> String {
+        let n = self.pick(&[
+            "18446744073709551616",
+            "340282366920938463463374607431768211456",
+            "-18446744073709551616",
+            "1e400",
+            "1E+999999999",
+            "-1",
+            "4000.0",
+            "4e3",
+            "0.0000000000000000000000001",
+            "",
+        ]);
+        let n = if n.is_empty() { "9".repeat(5_000) } else { n.to_string() };
+        format!(
+            r#"{{"jsonrpc":"2.0","id":10,"method":"tools/call","params":{{"name":"context.resolve","arguments":{{"cache":"minimal","query":"deployment","budget":{n}}}}}}}"#
+        )
+    }
+
+    fn duplicate_keys(&mut self) -> String {
+        let variants = [
+            r#"{"jsonrpc":"2.0","id":11,"id":12,"method":"ping"}"#,
+            r#"{"jsonrpc":"2.0","id":11,"method":"ping","method":"tools/list"}"#,
+            r#"{"jsonrpc":"2.0","jsonrpc":"1.0","id":11,"method":"ping"}"#,
+            r#"{"jsonrpc":"2.0","id":11,"method":"tools/call","params":{"name":"context.list_caches","name":"context.resolve","arguments":{}}}"#,
+            r#"{"jsonrpc":"2.0","id":11,"method":"tools/call","params":{"name":"context.resolve","arguments":{"cache":"minimal","cache":"../","query":"x","budget":1,"budget":-1}}}"#,
+            r#"{"jsonrpc":"2.0","id":11,"method":"tools/call","params":{},"params":{"name":"context.list_caches","arguments":{}}}"#,
+        ];
+        self.pick(&variants).to_string()
+    }
+
+    fn wrong_types(&mut self) -> String {
+        let field = self.pick(&[
+            r#""jsonrpc":2.0"#,
+            r#""jsonrpc":"1.0""#,
+            r#""method":1"#,
+            r#""method":null"#,
+            r#""method":["ping"]"#,
+            r#""params":"tools""#,
+            r#""params":[1,2,3]"#,
+            r#""params":null"#,
+        ]);
+        let base = self.pick(&[
+            r#""jsonrpc":"2.0","id":13,"method":"tools/list""#,
+            r#""jsonrpc":"2.0","id":13,"method":"tools/call","params":{"name":"context.list_caches","arguments":{}}"#,
+        ]);
+        match self.below(4) {
+            0 => format!("{{{base},{field}}}"),
+            1 => format!("[{{{base}}}]"),
+            2 => "[]".to_string(),
+            _ => self.value(2),
+        }
+    }
+
+    /// A valid envelope with a few byte-level mutations.
+    fn mutated(&mut self) -> Vec<u8> {
+        let mut bytes = self.envelope().into_bytes();
+        for _ in 0..=self.below(4) {
+            if bytes.is_empty() {
+                break;
+            }
+            let at = self.below(bytes.len());
+            match self.below(4) {
+                0 => {
+                    bytes.truncate(at);
+                }
+                1 => {
+                    bytes.remove(at);
+                }
+                2 => {
+                    let b = loop {
+                        let b = (self.rng.next_u64() & 0xff) as u8;
+                        if b != b'\n' {
+                            break b;
+                        }
+                    };
+                    bytes.insert(at, b);
+                }
+                _ => {
+                    let other = self.below(bytes.len());
+                    bytes.swap(at, other);
+                }
+            }
+        }
+        bytes
+    }
+}
@@ -1,3 +1,17 @@
+pub mod argv_fuzz;
+pub mod bench;
+pub mod cache_check;
 pub mod cli_runner;
+pub mod corpus;
+pub mod exit_code;
 pub mod fixture;
+pub mod invariants;
+pub mod jsonrpc_fuzz;
 pub mod mcp_runner;
+pub mod oracle;
+pub mod rng;
+pub mod rusage;
+pub mod schema_compat;
+pub mod source_tree;
+pub mod surface;
+pub mod transcript;
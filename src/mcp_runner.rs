@@ -1,14 +1,23 @@
+use crate::transcript::Recorder;
 use serde_json::Value;
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command, Stdio};
+use std::process::{Child, Command, ExitStatus, Stdio};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
 
 /// Runner that spawns an MCP server process and communicates via JSON-RPC over stdin/stdout.
+///
+/// Stdout is read line by line on a background thread, so reads can time out
+/// instead of blocking forever on a server that never answers.
 pub struct McpRunner {
     child: Child,
-    reader: BufReader<std::process::ChildStdout>,
+    lines: Receiver<Result<String, std::io::Error>>,
     next_id: AtomicU64,
+    recorder: Option<Recorder>,
+    /// Messages read while waiting for something else, in arrival order.
+    inbox: Vec<Value>,
 }
 
 impl McpRunner {
@@ -25,16 +34,50 @@ impl McpRunner {
             .stderr(Stdio::piped())
             .spawn()?;
 
+        // Nothing reads stderr, but a server logging every rejected input must
+        // never block on a full pipe and look hung.
+        let mut stderr = child.stderr.take().expect("stderr was piped");
+        std::thread::spawn(move || std::io::copy(&mut stderr, &mut std::io::sink()));
+
         let stdout = child.stdout.take().expect("stdout was piped");
-        let reader = BufReader::new(stdout);
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            loop {
+                let mut line = String::new();
+                match reader.read_line(&mut line) {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        if tx.send(Ok(line)).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        // Invalid UTF-8: the bad line is already consumed, so just report it.
+                        if tx.send(Err(e)).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
 
         Ok(Self {
             child,
-            reader,
+            lines,
             next_id: AtomicU64::new(1),
+            recorder: None,
+            inbox: Vec::new(),
         })
     }
 
+    /// Record every message written and every line read from now on to a
+    /// JSONL transcript at `path` (see [`crate::transcript::Transcript`]).
+    pub fn record_to(&mut self, path: &Path) -> Result<(), std::io::Error> {
+        self.recorder = Some(Recorder::create(path)?);
+        Ok(())
+    }
+
     /// Create an MCP runner from the `MCP_SERVER_BIN` environment variable.
     /// Returns `None` if the variable is not set.
     pub fn from_env(cache_root: &Path) -> Option<Result<Self, std::io::Error>> {
@@ -45,13 +88,69 @@ impl McpRunner {
 
     /// Send a raw JSON-RPC request string and read one response line.
     pub fn send(&mut self, request_json: &str) -> Result<String, std::io::Error> {
+        self.write(request_json)?;
+        self.read_line()
+    }
+
+    /// Write a raw JSON-RPC message without waiting for a response.
+    ///
+    /// Used to pipeline several requests before reading any of the responses.
+    pub fn write(&mut self, request_json: &str) -> Result<(), std::io::Error> {
+        self.write_bytes(request_json.as_bytes())
+    }
+
+    /// Write arbitrary bytes, which need not be valid UTF-8, followed by a newline.
+    pub fn write_bytes(&mut self, message: &[u8]) -> Result<(), std::io::Error> {
         let stdin = self.child.stdin.as_mut().expect("stdin was piped");
-        writeln!(stdin, "{}", request_json)?;
+        stdin.write_all(message)?;
+        stdin.write_all(b"\n")?;
         stdin.flush()?;
+        if let Some(recorder) = &mut self.recorder {
+            recorder.sent(message);
+        }
+        Ok(())
+    }
+
+    /// Read one line from the server's stdout. Returns an empty string at EOF.
+    pub fn read_line(&mut self) -> Result<String, std::io::Error> {
+        match self.lines.recv() {
+            Ok(line) => self.observe(line),
+            Err(_) => Ok(String::new()),
+        }
+    }
+
+    /// Like [`McpRunner::read_line`], but gives up after `timeout`.
+    /// Returns `None` if no complete line arrived in time.
+    pub fn read_line_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Option<String>, std::io::Error> {
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => self.observe(line).map(Some),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Ok(Some(String::new())),
+        }
+    }
+
+    /// The server's exit status if it has exited, without blocking.
+    pub fn try_wait(&mut self) -> Result<Option<ExitStatus>, std::io::Error> {
+        self.child.try_wait()
+    }
 
-        let mut line = String::new();
-        self.reader.read_line(&mut line)?;
-        Ok(line)
+    /// Build a `tools/call` request with a fresh id without sending it.
+    /// Returns the id and the serialized request.
+    pub fn tool_call_request(&self, name: &str, arguments: Value) -> (u64, String) {
+        let id = self.next_id();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {
+                "name": name,
+                "arguments": arguments
+            }
+        });
+        (id, request.to_string())
     }
 
     /// Send the `initialize` JSON-RPC handshake.
@@ -91,19 +190,115 @@ impl McpRunner {
         name: &str,
         arguments: Value,
     ) -> Result<String, std::io::Error> {
+        let (_, request) = self.tool_call_request(name, arguments);
+        self.send(&request)
+    }
+
+    /// Send a request for an arbitrary method with a fresh id and return the response.
+    pub fn request(&mut self, method: &str, params: Value) -> Result<String, std::io::Error> {
         let id = self.next_id();
         let request = serde_json::json!({
             "jsonrpc": "2.0",
             "id": id,
-            "method": "tools/call",
-            "params": {
-                "name": name,
-                "arguments": arguments
-            }
+            "method": method,
+            "params": params
         });
         self.send(&request.to_string())
     }
 
+    /// Write a request without waiting for its response; returns its id.
+    ///
+    /// Pair with [`McpRunner::wait_for`]. Anything read in between is kept
+    /// for [`McpRunner::take_notifications`] and [`McpRunner::take_responses`].
+    pub fn request_async(&mut self, method: &str, params: Value) -> Result<u64, std::io::Error> {
+        let id = self.next_id();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params
+        });
+        self.write(&request.to_string())?;
+        Ok(id)
+    }
+
+    /// Write a notification (a message without an id).
+    pub fn notify(&mut self, method: &str, params: Value) -> Result<(), std::io::Error> {
+        let notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params
+        });
+        self.write(&notification.to_string())
+    }
+
+    /// Wait up to `timeout` for the response to request `id`, collecting every
+    /// other message read meanwhile. Returns `None` on timeout.
+    pub fn wait_for(&mut self, id: u64, timeout: Duration) -> Result<Option<Value>, std::io::Error> {
+        if let Some(i) = self.inbox.iter().position(|m| is_response_to(m, id)) {
+            return Ok(Some(self.inbox.remove(i)));
+        }
+        let deadline = Instant::now() + timeout;
+        while let Some(message) = self.next_message(deadline)? {
+            if is_response_to(&message, id) {
+                return Ok(Some(message));
+            }
+            self.inbox.push(message);
+        }
+        Ok(None)
+    }
+
+    /// Collect every message that arrives until the server has been silent
+    /// for `quiet`.
+    pub fn collect(&mut self, quiet: Duration) -> Result<(), std::io::Error> {
+        while let Some(message) = self.next_message(Instant::now() + quiet)? {
+            self.inbox.push(message);
+        }
+        Ok(())
+    }
+
+    /// Remove and return the collected notifications, in arrival order.
+    pub fn take_notifications(&mut self) -> Vec<Value> {
+        self.take_where(|m| m.get("id").is_none() && m.get("method").is_some())
+    }
+
+    /// Remove and return collected responses to request `id`.
+    pub fn take_responses(&mut self, id: u64) -> Vec<Value> {
+        self.take_where(|m| is_response_to(m, id))
+    }
+
+    /// Remove and return everything collected, in arrival order.
+    pub fn take_all(&mut self) -> Vec<Value> {
+        std::mem::take(&mut self.inbox)
+    }
+
+    fn take_where(&mut self, pred: impl Fn(&Value) -> bool) -> Vec<Value> {
+        let (taken, kept) = std::mem::take(&mut self.inbox).into_iter().partition(|m| pred(m));
+        self.inbox = kept;
+        taken
+    }
+
+    /// Read and parse the next message, or `None` once `deadline` passes.
+    fn next_message(&mut self, deadline: Instant) -> Result<Option<Value>, std::io::Error> {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let line = match self.read_line_timeout(remaining)? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        if line.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "server closed stdout",
+            ));
+        }
+        serde_json::from_str(line.trim()).map(Some).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("stdout line is not JSON ({e}): {}", line.trim()),
+            )
+        })
+    }
+
     /// Send a request with an unknown method to test error handling.
     pub fn send_unknown_method(&mut self) -> Result<String, std::io::Error> {
         let id = self.next_id();
@@ -116,15 +311,41 @@ impl McpRunner {
         self.send(&request.to_string())
     }
 
+    fn observe(
+        &mut self,
+        line: Result<String, std::io::Error>,
+    ) -> Result<String, std::io::Error> {
+        if let (Some(recorder), Ok(line)) = (&mut self.recorder, &line) {
+            recorder.received(line);
+        }
+        line
+    }
+
     fn next_id(&self) -> u64 {
         self.next_id.fetch_add(1, Ordering::SeqCst)
     }
 }
 
+fn is_response_to(message: &Value, id: u64) -> bool {
+    message.get("method").is_none() && message.get("id") == Some(&Value::from(id))
+}
+
+/// How long a server may take to exit after stdin closes before it is killed.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
 impl Drop for McpRunner {
     fn drop(&mut self) {
-        // Close stdin to signal the server to shut down, then wait.
+        // Close stdin to signal the server to shut down, then wait, killing it
+        // if it is wedged so a failing test unwinds instead of hanging.
         drop(self.child.stdin.take());
+        let deadline = Instant::now() + SHUTDOWN_GRACE;
+        while Instant::now() < deadline {
+            match self.child.try_wait() {
+                Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+                _ => return,
+            }
+        }
+        let _ = self.child.kill();
         let _ = self.child.wait();
     }
 }
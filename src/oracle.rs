@@ -0,0 +1,57 @@
+use std::cmp::Ordering;
+
+/// Reference model of v0 ranking, used to cross-check `resolve` output.
+///
+/// - Query terms and document words are split on Unicode whitespace and lowercased.
+/// - `term_matches` counts document words equal to any query term.
+/// - `score = term_matches / total_words` as `f32`, or `0.0` for an empty document.
+/// - `tokens = ceil(content bytes / 4)`.
+/// - Documents are ordered by `(score DESC, id ASC)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedDocument {
+    pub id: String,
+    pub score: f32,
+    pub tokens: u64,
+    pub term_matches: u64,
+    pub total_words: u64,
+}
+
+/// Rank `(id, content)` documents against `query`.
+pub fn rank<'a>(
+    documents: impl IntoIterator<Item = (&'a str, &'a str)>,
+    query: &str,
+) -> Vec<RankedDocument> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let mut ranked: Vec<RankedDocument> = documents
+        .into_iter()
+        .map(|(id, content)| {
+            let mut total_words = 0u64;
+            let mut term_matches = 0u64;
+            for word in content.split_whitespace() {
+                total_words += 1;
+                if terms.contains(&word.to_lowercase()) {
+                    term_matches += 1;
+                }
+            }
+            let score = if total_words == 0 {
+                0.0
+            } else {
+                term_matches as f32 / total_words as f32
+            };
+            RankedDocument {
+                id: id.to_string(),
+                score,
+                tokens: (content.len() as u64).div_ceil(4),
+                term_matches,
+                total_words,
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    ranked
+}
@@ -0,0 +1,64 @@
+use std::process::{Child, ExitStatus};
+use std::time::Duration;
+
+/// CPU time and memory used by a reaped child process.
+///
+/// All zero on platforms without `wait4(2)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    pub user: Duration,
+    pub sys: Duration,
+    /// Peak resident set size in KiB.
+    pub max_rss_kb: u64,
+}
+
+impl ResourceUsage {
+    /// User plus system CPU time.
+    pub fn cpu(&self) -> Duration {
+        self.user + self.sys
+    }
+}
+
+/// Wait for `child` and collect its own rusage (not the harness's).
+///
+/// The child is reaped here, so `Child::wait` must not be called on it afterwards.
+#[cfg(unix)]
+pub fn wait(child: &mut Child) -> Result<(ExitStatus, ResourceUsage), std::io::Error> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let pid = child.id() as libc::pid_t;
+    let mut status: libc::c_int = 0;
+    // SAFETY: rusage is plain old data; wait4 fills it in for the reaped child.
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: `pid` is our unreaped child; the out-pointers are valid for writes.
+        let rc = unsafe { libc::wait4(pid, &mut status, 0, &mut usage) };
+        if rc == pid {
+            break;
+        }
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+    let tv = |t: libc::timeval| Duration::new(t.tv_sec as u64, t.tv_usec as u32 * 1000);
+    // ru_maxrss is KiB on Linux but bytes on macOS.
+    let max_rss_kb = if cfg!(target_os = "macos") {
+        usage.ru_maxrss as u64 / 1024
+    } else {
+        usage.ru_maxrss as u64
+    };
+    Ok((
+        ExitStatus::from_raw(status),
+        ResourceUsage {
+            user: tv(usage.ru_utime),
+            sys: tv(usage.ru_stime),
+            max_rss_kb,
+        },
+    ))
+}
+
+#[cfg(not(unix))]
+pub fn wait(child: &mut Child) -> Result<(ExitStatus, ResourceUsage), std::io::Error> {
+    Ok((child.wait()?, ResourceUsage::default()))
+}
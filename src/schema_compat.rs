@@ -0,0 +1,108 @@
+use crate::surface::{self, Direction, Severity};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// Who a schema change breaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Writers of documents valid under the old schema, which the new schema
+    /// may now reject: newly required fields, narrowed enums, tightened
+    /// bounds, `additionalProperties` turned off, properties removed from a
+    /// closed object, constraint keywords added.
+    Producer,
+    /// Readers written against the old schema, which may now see documents
+    /// they never expected: removed required fields, widened enums or types,
+    /// relaxed bounds, `additionalProperties` turned on, properties added to a
+    /// closed object or removed from an open one, constraint keywords removed.
+    Consumer,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Producer => "producer",
+            Self::Consumer => "consumer",
+        })
+    }
+}
+
+/// One backward-compatibility violation between two versions of a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub role: Role,
+    /// Location inside the schema, e.g. `#.properties.error.properties.code`.
+    pub path: String,
+    pub description: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[breaks {}s] {}: {}", self.role, self.path, self.description)
+    }
+}
+
+/// Report every way `new` breaks producers or consumers of `old`.
+///
+/// A change that both narrows and widens (a renamed or retyped property) is
+/// reported once for each role.
+pub fn check(old: &Value, new: &Value) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for (role, dir) in [(Role::Producer, Direction::Input), (Role::Consumer, Direction::Output)] {
+        for change in surface::diff_schemas(old, new, dir) {
+            if change.severity == Severity::Breaking {
+                violations.push(Violation {
+                    role,
+                    path: change.path,
+                    description: change.description,
+                });
+            }
+        }
+    }
+    violations
+}
+
+/// Load every `*.schema.json` in `dir`, keyed by name without the suffix.
+pub fn load_dir(dir: &Path) -> Result<BTreeMap<String, Value>, String> {
+    let mut schemas = BTreeMap::new();
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let Some(name) = file_name.strip_suffix(".schema.json") else {
+            continue;
+        };
+        let path = entry.path();
+        let content =
+            std::fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let schema = serde_json::from_str(&content).map_err(|e| format!("{}: {e}", path.display()))?;
+        schemas.insert(name.to_string(), schema);
+    }
+    Ok(schemas)
+}
+
+/// Check every schema of one contract version against the next, keyed by
+/// schema name. A schema that disappears breaks both roles.
+pub fn check_sets(
+    old: &BTreeMap<String, Value>,
+    new: &BTreeMap<String, Value>,
+) -> BTreeMap<String, Vec<Violation>> {
+    let mut report = BTreeMap::new();
+    for (name, schema) in old {
+        let violations = match new.get(name) {
+            Some(current) => check(schema, current),
+            None => [Role::Producer, Role::Consumer]
+                .into_iter()
+                .map(|role| Violation {
+                    role,
+                    path: "#".to_string(),
+                    description: "schema removed".to_string(),
+                })
+                .collect(),
+        };
+        if !violations.is_empty() {
+            report.insert(name.clone(), violations);
+        }
+    }
+    report
+}
@@ -0,0 +1,154 @@
+use crate::rng::Rng;
+use std::path::{Path, PathBuf};
+
+/// An in-memory set of source files keyed by path relative to the sources root.
+///
+/// Used to lay the same logical document set down on disk in different ways
+/// (creation order, mtimes, hardlinks) so `context build` can be checked for
+/// independence from filesystem enumeration order and metadata.
+#[derive(Debug, Clone, Default)]
+pub struct SourceTree {
+    files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+/// Order in which files (and their parent directories) are created on disk.
+#[derive(Debug, Clone, Copy)]
+pub enum FileOrder {
+    Sorted,
+    Reversed,
+    Shuffled(u64),
+}
+
+/// Modification times applied to each file after it is written.
+#[derive(Debug, Clone, Copy)]
+pub enum Mtimes {
+    /// Leave whatever the filesystem assigned at creation.
+    Untouched,
+    /// The n-th created file gets `start + n * step` seconds since the epoch.
+    Sequential { start: i64, step: i64 },
+}
+
+/// How file contents reach the destination tree.
+#[derive(Debug, Clone)]
+pub enum Linking {
+    /// Write a fresh copy of every file.
+    Copied,
+    /// Hardlink every file to the same relative path under an existing tree.
+    Hardlinked(PathBuf),
+}
+
+/// Options for [`SourceTree::write`].
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    pub order: FileOrder,
+    pub mtimes: Mtimes,
+    pub linking: Linking,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            order: FileOrder::Sorted,
+            mtimes: Mtimes::Untouched,
+            linking: Linking::Copied,
+        }
+    }
+}
+
+impl SourceTree {
+    /// Create an empty tree.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read every regular file under `root`, recursively.
+    pub fn read(root: &Path) -> Result<Self, std::io::Error> {
+        let mut tree = Self::new();
+        tree.read_dir(root, root, Path::new(""))?;
+        Ok(tree)
+    }
+
+    /// Read every regular file under `root` and place it below `prefix` in this tree.
+    pub fn add_dir(&mut self, root: &Path, prefix: &Path) -> Result<(), std::io::Error> {
+        self.read_dir(root, root, prefix)
+    }
+
+    fn read_dir(&mut self, root: &Path, dir: &Path, prefix: &Path) -> Result<(), std::io::Error> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.read_dir(root, &path, prefix)?;
+            } else if path.is_file() {
+                let rel = path.strip_prefix(root).expect("entry is under root");
+                self.insert(prefix.join(rel), std::fs::read(&path)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Add or replace a file at a relative path.
+    pub fn insert(&mut self, rel: impl Into<PathBuf>, content: impl Into<Vec<u8>>) {
+        let rel = rel.into();
+        let content = content.into();
+        match self.files.binary_search_by(|(p, _)| p.cmp(&rel)) {
+            Ok(i) => self.files[i].1 = content,
+            Err(i) => self.files.insert(i, (rel, content)),
+        }
+    }
+
+    /// Relative paths and contents, sorted by path.
+    pub fn files(&self) -> &[(PathBuf, Vec<u8>)] {
+        &self.files
+    }
+
+    /// Materialize the tree under `dest`, which must not contain any of its files yet.
+    pub fn write(&self, dest: &Path, opts: &WriteOptions) -> Result<(), std::io::Error> {
+        let mut order: Vec<&(PathBuf, Vec<u8>)> = self.files.iter().collect();
+        match opts.order {
+            FileOrder::Sorted => {}
+            FileOrder::Reversed => order.reverse(),
+            FileOrder::Shuffled(seed) => Rng::new(seed).shuffle(&mut order),
+        }
+
+        std::fs::create_dir_all(dest)?;
+        for (n, (rel, content)) in order.into_iter().enumerate() {
+            let path = dest.join(rel);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            match &opts.linking {
+                Linking::Copied => std::fs::write(&path, content)?,
+                Linking::Hardlinked(origin) => std::fs::hard_link(origin.join(rel), &path)?,
+            }
+            if let Mtimes::Sequential { start, step } = opts.mtimes {
+                set_mtime(&path, start + n as i64 * step)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn set_mtime(path: &Path, secs: i64) -> Result<(), std::io::Error> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let time = libc::timespec {
+        tv_sec: secs as libc::time_t,
+        tv_nsec: 0,
+    };
+    let times = [time, time];
+    // SAFETY: `c_path` is a valid NUL-terminated string and `times` holds two timespecs.
+    let rc = unsafe { libc::utimensat(libc::AT_FDCWD, c_path.as_ptr(), times.as_ptr(), 0) };
+    if rc == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+fn set_mtime(_path: &Path, _secs: i64) -> Result<(), std::io::Error> {
+    Ok(())
+}
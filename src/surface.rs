@@ -0,0 +1,596 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// How a change to the tool surface affects existing callers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Cannot break an existing caller: new tools, new optional inputs,
+    /// relaxed constraints, reworded descriptions.
+    Additive,
+    /// Can break an existing caller: removed or renamed tools and fields, new
+    /// required inputs, narrowed input types, widened output types.
+    Breaking,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Additive => "additive",
+            Self::Breaking => "breaking",
+        })
+    }
+}
+
+/// One classified difference, located by a path such as
+/// `context.resolve inputSchema.properties.budget`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub severity: Severity,
+    pub path: String,
+    pub description: String,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.severity, self.path, self.description)
+    }
+}
+
+/// Which side of the wire a schema describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Callers produce it (`inputSchema`): narrowing breaks.
+    Input,
+    /// Callers consume it (`outputSchema`): widening breaks.
+    Output,
+}
+
+impl Direction {
+    /// Severity of a change that lets more values through.
+    fn widened(self) -> Severity {
+        match self {
+            Self::Input => Severity::Additive,
+            Self::Output => Severity::Breaking,
+        }
+    }
+
+    /// Severity of a change that lets fewer values through.
+    fn narrowed(self) -> Severity {
+        match self {
+            Self::Input => Severity::Breaking,
+            Self::Output => Severity::Additive,
+        }
+    }
+}
+
+/// Sort a `tools/list` result's tools by name so order alone never differs.
+pub fn normalize(result: &Value) -> Value {
+    let mut result = result.clone();
+    if let Some(tools) = result.get_mut("tools").and_then(Value::as_array_mut) {
+        tools.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+    }
+    result
+}
+
+/// Classify every difference between two `tools/list` results.
+///
+/// Input schemas and output schemas are compared with opposite variance:
+/// narrowing what a tool accepts breaks callers, as does widening what it
+/// returns.
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let old_tools = by_name(old);
+    let new_tools = by_name(new);
+
+    for (name, tool) in &old_tools {
+        match new_tools.iter().find(|(n, _)| n == name) {
+            Some((_, current)) => diff_tool(name, tool, current, &mut changes),
+            None => push(&mut changes, Severity::Breaking, name, "tool removed".to_string()),
+        }
+    }
+    for (name, _) in &new_tools {
+        if !old_tools.iter().any(|(n, _)| n == name) {
+            push(&mut changes, Severity::Additive, name, "tool added".to_string());
+        }
+    }
+    changes
+}
+
+/// Classify every difference between two versions of one JSON Schema.
+///
+/// `dir` says who writes the described values: callers (`Input`) are broken
+/// by narrowing, readers (`Output`) by widening. The root `$id` names the
+/// schema's version, so it is expected to change and is ignored.
+pub fn diff_schemas(old: &Value, new: &Value, dir: Direction) -> Vec<Change> {
+    let without_id = |schema: &Value| {
+        let mut schema = schema.clone();
+        if let Some(root) = schema.as_object_mut() {
+            root.remove("$id");
+        }
+        schema
+    };
+    let mut changes = Vec::new();
+    diff_schema("#", &without_id(old), &without_id(new), dir, &mut changes);
+    changes
+}
+
+/// Whether any change in `changes` is breaking.
+pub fn has_breaking(changes: &[Change]) -> bool {
+    changes.iter().any(|c| c.severity == Severity::Breaking)
+}
+
+fn by_name(result: &Value) -> Vec<(String, &Value)> {
+    result["tools"]
+        .as_array()
+        .map(|tools| {
+            tools
+                .iter()
+                .map(|t| (t["name"].as_str().unwrap_or_default().to_string(), t))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn push(changes: &mut Vec<Change>, severity: Severity, path: &str, description: String) {
+    changes.push(Change {
+        severity,
+        path: path.to_string(),
+        description,
+    });
+}
+
+fn diff_tool(name: &str, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    for key in keys(old, new) {
+        let path = format!("{name} {key}");
+        let (a, b) = (old.get(&key), new.get(&key));
+        if a == b {
+            continue;
+        }
+        match key.as_str() {
+            "description" | "title" => {
+                push(changes, Severity::Additive, &path, "text changed".to_string())
+            }
+            "inputSchema" => diff_optional_schema(&path, a, b, Direction::Input, changes),
+            "outputSchema" => diff_optional_schema(&path, a, b, Direction::Output, changes),
+            "annotations" => diff_annotations(&path, a, b, changes),
+            _ => diff_unknown(&path, a, b, changes),
+        }
+    }
+}
+
+fn diff_optional_schema(
+    path: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    dir: Direction,
+    changes: &mut Vec<Change>,
+) {
+    match (old, new) {
+        (Some(a), Some(b)) => diff_schema(path, a, b, dir, changes),
+        // A new output schema only documents what was already returned.
+        (None, Some(_)) if dir == Direction::Output => {
+            push(changes, Severity::Additive, path, "schema added".to_string())
+        }
+        (None, Some(_)) => push(changes, Severity::Breaking, path, "schema added".to_string()),
+        (Some(_), None) => push(changes, Severity::Breaking, path, "schema removed".to_string()),
+        (None, None) => {}
+    }
+}
+
+/// Compare two JSON Schemas for the same value.
+fn diff_schema(path: &str, old: &Value, new: &Value, dir: Direction, changes: &mut Vec<Change>) {
+    if old == new {
+        return;
+    }
+    let (Some(a), Some(b)) = (old.as_object(), new.as_object()) else {
+        push(changes, Severity::Breaking, path, format!("schema changed from {old} to {new}"));
+        return;
+    };
+
+    diff_types(path, a, b, dir, changes);
+    diff_properties(path, a, b, dir, changes);
+    diff_required(path, a, b, dir, changes);
+    diff_enum(path, a, b, dir, changes);
+    diff_bounds(path, a, b, dir, changes);
+
+    match (a.get("items"), b.get("items")) {
+        (Some(x), Some(y)) => diff_schema(&format!("{path}.items"), x, y, dir, changes),
+        (None, None) => {}
+        (x, y) => push(
+            changes,
+            Severity::Breaking,
+            &format!("{path}.items"),
+            format!("changed from {} to {}", show(x), show(y)),
+        ),
+    }
+
+    let field = format!("{path}.additionalProperties");
+    let description = || {
+        format!(
+            "changed from {} to {}",
+            show(a.get("additionalProperties")),
+            show(b.get("additionalProperties"))
+        )
+    };
+    match (additional(a), additional(b)) {
+        (Additional::Schema(x), Additional::Schema(y)) => diff_schema(&field, x, y, dir, changes),
+        (x, y) if x == y => {}
+        (Additional::Any, _) | (_, Additional::Forbidden) => {
+            push(changes, dir.narrowed(), &field, description())
+        }
+        (Additional::Forbidden, _) | (_, Additional::Any) => {
+            push(changes, dir.widened(), &field, description())
+        }
+    }
+
+    const HANDLED: &[&str] = &[
+        "type",
+        "properties",
+        "required",
+        "enum",
+        "items",
+        "additionalProperties",
+        "minimum",
+        "maximum",
+        "exclusiveMinimum",
+        "exclusiveMaximum",
+        "minLength",
+        "maxLength",
+        "minItems",
+        "maxItems",
+        "description",
+        "title",
+        "default",
+        "examples",
+        "$schema",
+    ];
+    // Other keywords are not modelled. Each one only adds a constraint, so
+    // adding one narrows and removing one widens; changing one is treated as
+    // breaking both ways.
+    for key in keys(old, new) {
+        if HANDLED.contains(&key.as_str()) || a.get(&key) == b.get(&key) {
+            continue;
+        }
+        let severity = match (a.get(&key), b.get(&key)) {
+            (None, Some(_)) => dir.narrowed(),
+            (Some(_), None) => dir.widened(),
+            _ => Severity::Breaking,
+        };
+        push(
+            changes,
+            severity,
+            &format!("{path}.{key}"),
+            format!("changed from {} to {}", show(a.get(&key)), show(b.get(&key))),
+        );
+    }
+    for key in ["description", "title", "default", "examples"] {
+        if a.get(key) != b.get(key) {
+            push(
+                changes,
+                Severity::Additive,
+                &format!("{path}.{key}"),
+                "documentation changed".to_string(),
+            );
+        }
+    }
+}
+
+/// The set of JSON types a schema admits; `None` means unconstrained.
+fn types(schema: &Map<String, Value>) -> Option<BTreeSet<String>> {
+    let mut set: BTreeSet<String> = match schema.get("type")? {
+        Value::String(t) => [t.clone()].into(),
+        Value::Array(ts) => ts.iter().filter_map(|t| t.as_str().map(String::from)).collect(),
+        _ => return None,
+    };
+    // Every integer is a number.
+    if set.contains("number") {
+        set.remove("integer");
+    }
+    Some(set)
+}
+
+fn admits(set: &BTreeSet<String>, t: &str) -> bool {
+    set.contains(t) || (t == "integer" && set.contains("number"))
+}
+
+fn diff_types(
+    path: &str,
+    a: &Map<String, Value>,
+    b: &Map<String, Value>,
+    dir: Direction,
+    changes: &mut Vec<Change>,
+) {
+    let (old, new) = (types(a), types(b));
+    if old == new {
+        return;
+    }
+    let description = format!("type changed from {} to {}", show(a.get("type")), show(b.get("type")));
+    let severity = match (&old, &new) {
+        (None, Some(_)) => dir.narrowed(),
+        (Some(_), None) => dir.widened(),
+        (Some(x), Some(y)) if x.iter().all(|t| admits(y, t)) => dir.widened(),
+        (Some(x), Some(y)) if y.iter().all(|t| admits(x, t)) => dir.narrowed(),
+        _ => Severity::Breaking,
+    };
+    push(changes, severity, path, description);
+}
+
+fn diff_properties(
+    path: &str,
+    a: &Map<String, Value>,
+    b: &Map<String, Value>,
+    dir: Direction,
+    changes: &mut Vec<Change>,
+) {
+    let empty = Map::new();
+    let old = a.get("properties").and_then(Value::as_object).unwrap_or(&empty);
+    let new = b.get("properties").and_then(Value::as_object).unwrap_or(&empty);
+    let old_required = required(a);
+    let new_required = required(b);
+
+    let removed: Vec<&String> = old.keys().filter(|k| !new.contains_key(*k)).collect();
+    let added: Vec<&String> = new.keys().filter(|k| !old.contains_key(*k)).collect();
+
+    // A property that is added or removed moves between its own schema and
+    // `additionalProperties`, so its severity depends on what that admits.
+    let requiredness = |name: &str| {
+        match (old_required.contains(name), new_required.contains(name), dir) {
+            (false, true, Direction::Input) | (true, false, Direction::Output) => Severity::Breaking,
+            _ => Severity::Additive,
+        }
+    };
+    for name in &removed {
+        let field = format!("{path}.properties.{name}");
+        // A removed field whose schema reappears under a new name is a rename.
+        if let Some(to) = added.iter().find(|n| new[n.as_str()] == old[name.as_str()]) {
+            push(changes, Severity::Breaking, &field, format!("renamed to {to}"));
+            continue;
+        }
+        let moved = match additional(b) {
+            Additional::Any => dir.widened(),
+            Additional::Forbidden => dir.narrowed(),
+            Additional::Schema(rest) => moved(&old[name.as_str()], rest, dir),
+        };
+        let severity = moved.max(requiredness(name));
+        push(changes, severity, &field, "property removed".to_string());
+    }
+    for name in &added {
+        let is_rename = removed.iter().any(|r| old[r.as_str()] == new[name.as_str()]);
+        if is_rename {
+            continue;
+        }
+        let moved = match additional(a) {
+            // Declaring a property an open object already admitted narrows it,
+            // but no caller had reason to send it, so this counts as additive.
+            Additional::Any => Severity::Additive,
+            Additional::Forbidden => dir.widened(),
+            Additional::Schema(rest) => moved(rest, &new[name.as_str()], dir),
+        };
+        let severity = moved.max(requiredness(name));
+        let kind = if new_required.contains(name.as_str()) { "required" } else { "optional" };
+        push(
+            changes,
+            severity,
+            &format!("{path}.properties.{name}"),
+            format!("{kind} property added"),
+        );
+    }
+    for (name, schema) in old {
+        if let Some(current) = new.get(name) {
+            diff_schema(&format!("{path}.properties.{name}"), schema, current, dir, changes);
+        }
+    }
+    // Requiredness of properties present on both sides is handled here so
+    // added and removed properties are not reported twice.
+    for name in old.keys().filter(|k| new.contains_key(*k)) {
+        let (was, is) = (old_required.contains(name.as_str()), new_required.contains(name.as_str()));
+        let field = format!("{path}.properties.{name}");
+        match (was, is, dir) {
+            (false, true, Direction::Input) => {
+                push(changes, Severity::Breaking, &field, "now required".to_string())
+            }
+            (true, false, Direction::Input) => {
+                push(changes, Severity::Additive, &field, "no longer required".to_string())
+            }
+            (false, true, Direction::Output) => {
+                push(changes, Severity::Additive, &field, "now always present".to_string())
+            }
+            (true, false, Direction::Output) => {
+                push(changes, Severity::Breaking, &field, "no longer always present".to_string())
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Severity of values once valid against `from` having to be valid against `to`.
+fn moved(from: &Value, to: &Value, dir: Direction) -> Severity {
+    let mut changes = Vec::new();
+    diff_schema("", from, to, dir, &mut changes);
+    if has_breaking(&changes) {
+        Severity::Breaking
+    } else {
+        Severity::Additive
+    }
+}
+
+/// Requiredness of names not declared in `properties` on both sides.
+fn diff_required(
+    path: &str,
+    a: &Map<String, Value>,
+    b: &Map<String, Value>,
+    dir: Direction,
+    changes: &mut Vec<Change>,
+) {
+    let declared = |m: &Map<String, Value>, k: &str| {
+        m.get("properties").and_then(|p| p.get(k)).is_some()
+    };
+    let (old, new) = (required(a), required(b));
+    for name in new.difference(&old) {
+        if !(declared(a, name) || declared(b, name)) {
+            let severity = dir.narrowed();
+            push(changes, severity, &format!("{path}.required"), format!("{name} now required"));
+        }
+    }
+    for name in old.difference(&new) {
+        if !(declared(a, name) || declared(b, name)) {
+            let severity = dir.widened();
+            push(changes, severity, &format!("{path}.required"), format!("{name} no longer required"));
+        }
+    }
+}
+
+fn diff_enum(
+    path: &str,
+    a: &Map<String, Value>,
+    b: &Map<String, Value>,
+    dir: Direction,
+    changes: &mut Vec<Change>,
+) {
+    let values = |m: &Map<String, Value>| m.get("enum").and_then(Value::as_array).cloned();
+    match (values(a), values(b)) {
+        (Some(old), Some(new)) => {
+            for v in old.iter().filter(|v| !new.contains(v)) {
+                push(changes, dir.narrowed(), path, format!("enum value {v} removed"));
+            }
+            for v in new.iter().filter(|v| !old.contains(v)) {
+                push(changes, dir.widened(), path, format!("enum value {v} added"));
+            }
+        }
+        (None, Some(_)) => push(changes, dir.narrowed(), path, "enum added".to_string()),
+        (Some(_), None) => push(changes, dir.widened(), path, "enum removed".to_string()),
+        (None, None) => {}
+    }
+}
+
+fn diff_bounds(
+    path: &str,
+    a: &Map<String, Value>,
+    b: &Map<String, Value>,
+    dir: Direction,
+    changes: &mut Vec<Change>,
+) {
+    // (keyword, whether a larger value admits more)
+    const BOUNDS: &[(&str, bool)] = &[
+        ("minimum", false),
+        ("exclusiveMinimum", false),
+        ("minLength", false),
+        ("minItems", false),
+        ("maximum", true),
+        ("exclusiveMaximum", true),
+        ("maxLength", true),
+        ("maxItems", true),
+    ];
+    for &(key, larger_admits_more) in BOUNDS {
+        let (old, new) = (a.get(key).and_then(Value::as_f64), b.get(key).and_then(Value::as_f64));
+        let widened = match (old, new) {
+            (Some(x), Some(y)) if x == y => continue,
+            (Some(x), Some(y)) => (y > x) == larger_admits_more,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (None, None) => continue,
+        };
+        let severity = if widened { dir.widened() } else { dir.narrowed() };
+        push(
+            changes,
+            severity,
+            &format!("{path}.{key}"),
+            format!("changed from {} to {}", show(a.get(key)), show(b.get(key))),
+        );
+    }
+}
+
+/// Tool annotations are hints about side effects; a change is breaking when
+/// it makes the tool look less safe than callers were told.
+fn diff_annotations(path: &str, old: Option<&Value>, new: Option<&Value>, changes: &mut Vec<Change>) {
+    // (hint, default when absent, the value that is safer)
+    const HINTS: &[(&str, bool, bool)] = &[
+        ("readOnlyHint", false, true),
+        ("destructiveHint", true, false),
+        ("idempotentHint", false, true),
+        ("openWorldHint", true, false),
+    ];
+    let get = |v: Option<&Value>, key: &str| v.and_then(|v| v.get(key)).cloned();
+    for &(hint, default, safe) in HINTS {
+        let value = |v: Option<&Value>| get(v, hint).and_then(|h| h.as_bool()).unwrap_or(default);
+        let (was, is) = (value(old), value(new));
+        if was == is {
+            if get(old, hint) != get(new, hint) {
+                push(
+                    changes,
+                    Severity::Additive,
+                    &format!("{path}.{hint}"),
+                    "made explicit or implicit without changing meaning".to_string(),
+                );
+            }
+            continue;
+        }
+        let severity = if is == safe { Severity::Additive } else { Severity::Breaking };
+        push(changes, severity, &format!("{path}.{hint}"), format!("changed from {was} to {is}"));
+    }
+    let empty = Value::Object(Map::new());
+    let (a, b) = (old.unwrap_or(&empty), new.unwrap_or(&empty));
+    for key in keys(a, b) {
+        if HINTS.iter().any(|(h, _, _)| *h == key) || a.get(&key) == b.get(&key) {
+            continue;
+        }
+        let severity = if key == "title" { Severity::Additive } else { Severity::Breaking };
+        push(
+            changes,
+            severity,
+            &format!("{path}.{key}"),
+            format!("changed from {} to {}", show(a.get(&key)), show(b.get(&key))),
+        );
+    }
+}
+
+/// Fields this module does not know about: adding one is additive, anything
+/// else is breaking until someone decides otherwise.
+fn diff_unknown(path: &str, old: Option<&Value>, new: Option<&Value>, changes: &mut Vec<Change>) {
+    let severity = if old.is_none() { Severity::Additive } else { Severity::Breaking };
+    push(changes, severity, path, format!("changed from {} to {}", show(old), show(new)));
+}
+
+fn required(schema: &Map<String, Value>) -> BTreeSet<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// What a schema's `additionalProperties` admits for undeclared properties.
+#[derive(PartialEq)]
+enum Additional<'a> {
+    /// Anything: absent, `true` or `{}`, as JSON Schema defaults to.
+    Any,
+    /// Nothing: `false`.
+    Forbidden,
+    /// Values valid against this schema.
+    Schema(&'a Value),
+}
+
+fn additional(schema: &Map<String, Value>) -> Additional<'_> {
+    match schema.get("additionalProperties") {
+        None | Some(Value::Bool(true)) => Additional::Any,
+        Some(Value::Bool(false)) => Additional::Forbidden,
+        Some(Value::Object(m)) if m.is_empty() => Additional::Any,
+        Some(s) => Additional::Schema(s),
+    }
+}
+
+/// Union of the object keys of `a` and `b`, sorted.
+fn keys(a: &Value, b: &Value) -> BTreeSet<String> {
+    let names = |v: &Value| -> Vec<String> {
+        v.as_object().map(|m| m.keys().cloned().collect()).unwrap_or_default()
+    };
+    names(a).into_iter().chain(names(b)).collect()
+}
+
+fn show(v: Option<&Value>) -> String {
+    match v {
+        Some(v) => v.to_string(),
+        None => "absent".to_string(),
+    }
+}
@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// JSON pointers into responses that are redacted in every transcript.
+pub const DEFAULT_REDACTIONS: &[&str] = &["/result/serverInfo/version"];
+
+/// Placeholder that replaces redacted values on both sides of a comparison.
+pub const REDACTED: &str = "<redacted>";
+
+/// One line of a transcript: a message written to the server or read back.
+///
+/// Sent lines that JSON would not reproduce exactly, and received lines that
+/// are not JSON, are kept as JSON strings; a JSON-RPC message is never a bare
+/// string, so the two cannot be confused.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Entry {
+    Send(Value),
+    Recv(Value),
+    /// A sent line that is not valid UTF-8, base64-encoded.
+    #[serde(rename = "send_base64")]
+    SendBase64(String),
+}
+
+impl Entry {
+    /// Store a line written to the server so that [`Entry::sent_bytes`]
+    /// reproduces it byte for byte: as JSON only if compact re-serialization
+    /// gives back the same bytes, otherwise as the raw line.
+    pub fn sent(line: &[u8]) -> Self {
+        let Ok(text) = std::str::from_utf8(line) else {
+            return Self::SendBase64(BASE64.encode(line));
+        };
+        match serde_json::from_str::<Value>(text) {
+            Ok(v) if !v.is_string() && serde_json::to_vec(&v).ok().as_deref() == Some(line) => {
+                Self::Send(v)
+            }
+            _ => Self::Send(Value::String(text.to_string())),
+        }
+    }
+
+    /// The bytes to write for a recorded send, or `None` for a `recv`.
+    pub fn sent_bytes(&self) -> Option<Vec<u8>> {
+        match self {
+            Self::Send(Value::String(raw)) => Some(raw.clone().into_bytes()),
+            Self::Send(message) => Some(message.to_string().into_bytes()),
+            Self::SendBase64(encoded) => BASE64.decode(encoded).ok(),
+            Self::Recv(_) => None,
+        }
+    }
+}
+
+/// Parse one line read from the server as stored in a transcript: JSON if it
+/// parses, otherwise the line itself as a JSON string.
+pub fn message(line: &str) -> Value {
+    let text = line.trim_end_matches(['\r', '\n']);
+    serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+}
+
+/// Optional first line of a transcript file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Header {
+    /// Extra JSON pointers to redact in this transcript's responses.
+    #[serde(default)]
+    redact: Vec<String>,
+}
+
+/// A recorded MCP session: `fixtures/v0/transcripts/*.jsonl`.
+///
+/// The format is JSON Lines. An optional header `{"redact": [pointers]}`
+/// comes first, followed by one `{"send": message}`, `{"send_base64": bytes}`
+/// or `{"recv": message}` per line in the order the messages crossed the wire.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    /// JSON pointers redacted on top of [`DEFAULT_REDACTIONS`].
+    pub redact: Vec<String>,
+    pub entries: Vec<Entry>,
+}
+
+impl Transcript {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let mut transcript = Self::default();
+        for (n, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let at = |e: serde_json::Error| format!("{}:{}: {e}", path.display(), n + 1);
+            let value: Value = serde_json::from_str(line).map_err(at)?;
+            if n == 0 && value.get("redact").is_some() {
+                let header: Header = serde_json::from_value(value).map_err(at)?;
+                transcript.redact = header.redact;
+            } else {
+                transcript.entries.push(serde_json::from_value(value).map_err(at)?);
+            }
+        }
+        Ok(transcript)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut out = String::new();
+        if !self.redact.is_empty() {
+            let header = Header {
+                redact: self.redact.clone(),
+            };
+            out += &serde_json::to_string(&header).unwrap();
+            out.push('\n');
+        }
+        for entry in &self.entries {
+            out += &serde_json::to_string(entry).unwrap();
+            out.push('\n');
+        }
+        std::fs::write(path, out).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// All pointers to redact: the defaults plus this transcript's own.
+    pub fn redactions(&self) -> Vec<&str> {
+        DEFAULT_REDACTIONS
+            .iter()
+            .copied()
+            .chain(self.redact.iter().map(String::as_str))
+            .collect()
+    }
+}
+
+/// A received message in the form transcripts compare: tool-result `text`
+/// content that holds JSON is parsed in place, so key order and number
+/// formatting inside it never matter and pointers such as
+/// `/result/content/0/text/selection` can redact fields within it.
+pub fn expand(message: &Value) -> Value {
+    let mut message = message.clone();
+    let content = message
+        .pointer_mut("/result/content")
+        .and_then(Value::as_array_mut);
+    for item in content.into_iter().flatten() {
+        if item["type"] != "text" {
+            continue;
+        }
+        if let Some(text) = item.get_mut("text") {
+            if let Some(parsed) = text.as_str().and_then(|t| serde_json::from_str(t).ok()) {
+                *text = parsed;
+            }
+        }
+    }
+    message
+}
+
+/// Replace the value at each pointer that exists in `message` with [`REDACTED`].
+pub fn redact(message: &Value, pointers: &[&str]) -> Value {
+    let mut message = message.clone();
+    for pointer in pointers {
+        if let Some(v) = message.pointer_mut(pointer) {
+            *v = Value::String(REDACTED.to_string());
+        }
+    }
+    message
+}
+
+/// Appends every message of a live session to a transcript file as it
+/// happens, so a session that ends in a crash is still recorded.
+pub(crate) struct Recorder {
+    out: BufWriter<File>,
+}
+
+impl Recorder {
+    pub(crate) fn create(path: &Path) -> Result<Self, std::io::Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self {
+            out: BufWriter::new(File::create(path)?),
+        })
+    }
+
+    pub(crate) fn sent(&mut self, line: &[u8]) {
+        self.append(&Entry::sent(line));
+    }
+
+    pub(crate) fn received(&mut self, line: &str) {
+        self.append(&Entry::Recv(message(line)));
+    }
+
+    fn append(&mut self, entry: &Entry) {
+        // Recording is best effort; it must never fail the session under test.
+        let _ = serde_json::to_writer(&mut self.out, entry);
+        let _ = self.out.write_all(b"\n");
+        let _ = self.out.flush();
+    }
+}
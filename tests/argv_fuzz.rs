@@ -0,0 +1,106 @@
+//! Argument-level fuzzing of the `context` CLI.
+//!
+//! `context_compat::argv_fuzz` derives malformed argv from valid `build`,
+//! `resolve` and `inspect` invocations: unknown and duplicated flags, missing
+//! flags and values, unparseable `--budget` values, control-character and very
+//! long queries, and arguments that are not valid UTF-8. The oracle: the exit
+//! code is always a documented argument error (1, 2 or 3), never an internal
+//! error or a signal, and stdout stays empty on failure. Non-UTF-8 paths are
+//! still valid OS paths, so they may also fail with a cache or I/O error.
+//!
+//! The sequence is seeded (`CONTEXT_FUZZ_SEED`, `CONTEXT_FUZZ_CASES`).
+
+use context_compat::argv_fuzz::{ArgvFuzzer, ArgvPaths};
+use context_compat::cli_runner::CliRunner;
+use context_compat::exit_code::ExitCode;
+use context_compat::fixture;
+use std::path::PathBuf;
+
+const DEFAULT_SEED: u64 = 0x6172_6776_6675_7a7a;
+const DEFAULT_CASES: usize = 300;
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    match std::env::var(name) {
+        Ok(s) => s
+            .parse()
+            .unwrap_or_else(|_| panic!("{name} must be an integer, got {s:?}")),
+        Err(_) => default,
+    }
+}
+
+fn paths(output: PathBuf) -> ArgvPaths {
+    ArgvPaths {
+        cache: fixture::cache_path("minimal"),
+        sources: fixture::documents_path("minimal"),
+        output,
+    }
+}
+
+#[test]
+fn argv_fuzz() {
+    let runner = match CliRunner::from_env() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+    let seed = env_or("CONTEXT_FUZZ_SEED", DEFAULT_SEED);
+    let cases = env_or("CONTEXT_FUZZ_CASES", DEFAULT_CASES);
+    eprintln!("argv fuzz: seed {seed:#x}, {cases} cases");
+
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("out");
+    let mut fuzzer = ArgvFuzzer::new(seed, paths(output.clone()));
+    for index in 0..cases {
+        let case = fuzzer.next_case();
+        // A `build` that got as far as writing must not affect this case.
+        let _ = std::fs::remove_dir_all(&output);
+        let ctx = format!("case {index} ({}) with seed {seed:#x}", case.mutation);
+        let out = runner.run(&case.args).unwrap();
+
+        let code = out
+            .exit()
+            .unwrap_or_else(|| panic!("{ctx}: undocumented termination: {}", out.describe()));
+        assert_ne!(code, ExitCode::Internal, "{ctx}: {}", out.describe());
+        assert!(
+            case.expect.allows(code),
+            "{ctx}: unexpected exit {code} ({:?}): {}",
+            case.expect,
+            out.describe()
+        );
+        if code == ExitCode::Success {
+            if case.args[0] == "build" {
+                continue;
+            }
+            serde_json::from_str::<serde_json::Value>(out.stdout.trim())
+                .unwrap_or_else(|e| panic!("{ctx}: stdout is not JSON ({e})"));
+        } else {
+            assert!(
+                out.stdout.is_empty(),
+                "{ctx}: failing run wrote stdout: {}",
+                out.stdout.chars().take(200).collect::<String>()
+            );
+        }
+    }
+}
+
+/// The generator is deterministic and covers every command.
+#[test]
+fn argv_fuzzer_is_deterministic() {
+    let mut a = ArgvFuzzer::new(7, paths(PathBuf::from("out")));
+    let mut b = ArgvFuzzer::new(7, paths(PathBuf::from("out")));
+    let mut commands = std::collections::BTreeSet::new();
+    for _ in 0..300 {
+        let (x, y) = (a.next_case(), b.next_case());
+        assert_eq!(x.mutation, y.mutation);
+        assert_eq!(x.args, y.args);
+        assert_eq!(x.expect, y.expect);
+        commands.insert(x.args[0].clone());
+    }
+    assert_eq!(
+        commands.len(),
+        3,
+        "not every command generated: {commands:?}"
+    );
+}
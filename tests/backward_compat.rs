@@ -2,19 +2,13 @@
 //! Also tests error boundaries: unsupported versions, IO failures, exit code contracts.
 
 use context_compat::cli_runner::CliRunner;
+use context_compat::exit_code::ExitCode;
 use context_compat::fixture;
 
 fn cli() -> Option<CliRunner> {
     CliRunner::from_env()
 }
 
-// --- Frozen CLI exit codes (per cli_spec.md) ---
-
-const EXIT_SUCCESS: i32 = 0;
-const EXIT_CACHE_MISSING: i32 = 4;
-const EXIT_CACHE_INVALID: i32 = 5;
-const EXIT_IO_ERROR: i32 = 6;
-
 // --- v0 cache compatibility ---
 
 /// Pre-built v0 minimal cache loads and produces valid resolve output.
@@ -32,7 +26,7 @@ fn v0_cache_minimal_resolves() {
     let q = fixture::query("basic");
     let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
 
-    assert_eq!(out.exit_code, EXIT_SUCCESS, "resolve failed on v0 cache: {}", out.stderr);
+    assert_eq!(out.exit(), Some(ExitCode::Success), "resolve failed on v0 cache: {}", out.describe());
 
     let v: serde_json::Value = serde_json::from_str(out.stdout.trim()).unwrap();
     assert!(v["documents"].is_array());
@@ -55,7 +49,7 @@ fn v0_cache_realistic_resolves() {
     let q = fixture::query("basic");
     let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
 
-    assert_eq!(out.exit_code, EXIT_SUCCESS, "resolve failed on v0 cache: {}", out.stderr);
+    assert_eq!(out.exit(), Some(ExitCode::Success), "resolve failed on v0 cache: {}", out.describe());
 
     let v: serde_json::Value = serde_json::from_str(out.stdout.trim()).unwrap();
     assert!(v["documents"].is_array());
@@ -77,7 +71,7 @@ fn v0_cache_inspect_valid() {
         let cache = fixture::cache_path(name);
         let out = runner.inspect(&cache).unwrap();
 
-        assert_eq!(out.exit_code, EXIT_SUCCESS, "inspect failed on v0 {name} cache: {}", out.stderr);
+        assert_eq!(out.exit(), Some(ExitCode::Success), "inspect failed on v0 {name} cache: {}", out.describe());
 
         let v: serde_json::Value = serde_json::from_str(out.stdout.trim()).unwrap();
         assert_eq!(v["valid"], true, "v0 {name} cache should be valid");
@@ -103,9 +97,9 @@ fn v0_cache_all_queries() {
             let q = fixture::query(query_name);
             let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
             assert_eq!(
-                out.exit_code, EXIT_SUCCESS,
+                out.exit(), Some(ExitCode::Success),
                 "resolve failed for {cache_name}/{query_name}: {}",
-                out.stderr
+                out.describe()
             );
         }
     }
@@ -129,16 +123,18 @@ fn exit_code_cache_missing() {
 
     let out = runner.inspect(&missing).unwrap();
     assert_eq!(
-        out.exit_code, EXIT_CACHE_MISSING,
-        "inspect of missing cache should return exit code {EXIT_CACHE_MISSING}, got {}",
-        out.exit_code
+        out.exit(), Some(ExitCode::CacheMissing),
+        "inspect of missing cache should return exit code {}, got {}",
+        ExitCode::CacheMissing,
+        out.describe()
     );
 
     let out = runner.resolve(&missing, "test", 100).unwrap();
     assert_eq!(
-        out.exit_code, EXIT_CACHE_MISSING,
-        "resolve of missing cache should return exit code {EXIT_CACHE_MISSING}, got {}",
-        out.exit_code
+        out.exit(), Some(ExitCode::CacheMissing),
+        "resolve of missing cache should return exit code {}, got {}",
+        ExitCode::CacheMissing,
+        out.describe()
     );
 }
 
@@ -160,9 +156,10 @@ fn exit_code_cache_invalid() {
 
     let out = runner.resolve(&cache, "test", 100).unwrap();
     assert_eq!(
-        out.exit_code, EXIT_CACHE_INVALID,
-        "resolve of corrupt cache should return exit code {EXIT_CACHE_INVALID}, got {}",
-        out.exit_code
+        out.exit(), Some(ExitCode::CacheInvalid),
+        "resolve of corrupt cache should return exit code {}, got {}",
+        ExitCode::CacheInvalid,
+        out.describe()
     );
 }
 
@@ -184,15 +181,106 @@ fn exit_code_missing_manifest() {
 
     let out = runner.resolve(&cache, "test", 100).unwrap();
     // Missing manifest file inside an existing dir should still error
-    assert_ne!(out.exit_code, EXIT_SUCCESS, "resolve should fail without manifest");
+    assert_ne!(out.exit(), Some(ExitCode::Success), "resolve should fail without manifest");
     // Could be CACHE_MISSING (file not found) or CACHE_INVALID
     assert!(
-        out.exit_code == EXIT_CACHE_MISSING || out.exit_code == EXIT_CACHE_INVALID,
-        "expected exit code {EXIT_CACHE_MISSING} or {EXIT_CACHE_INVALID}, got {}",
-        out.exit_code
+        out.exit() == Some(ExitCode::CacheMissing) || out.exit() == Some(ExitCode::CacheInvalid),
+        "expected exit code {} or {}, got {}",
+        ExitCode::CacheMissing,
+        ExitCode::CacheInvalid,
+        out.describe()
+    );
+}
+
+/// Every exit status the binary produces must be one of the documented codes.
+///
+/// Exercises success, usage, validation, cache and build failures; which code
+/// each case gets is checked elsewhere, this only rejects codes outside the
+/// contract (and signal deaths).
+#[test]
+fn exit_codes_are_documented() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache = fixture::cache_path("minimal");
+    let cache = cache.to_str().unwrap();
+    let missing = dir.path().join("missing");
+    let missing = missing.to_str().unwrap();
+    let not_a_dir = dir.path().join("file");
+    std::fs::write(&not_a_dir, "not a cache").unwrap();
+    let not_a_dir = not_a_dir.to_str().unwrap();
+    let scratch = dir.path().join("built");
+    let scratch = scratch.to_str().unwrap();
+    let sources = fixture::documents_path("minimal");
+    let sources = sources.to_str().unwrap();
+
+    let cases: &[&[&str]] = &[
+        &[],
+        &["--help"],
+        &["--version"],
+        &["frobnicate"],
+        &["resolve"],
+        &["resolve", "--cache", cache],
+        &["resolve", "--cache", cache, "--query", "deployment"],
+        &["resolve", "--cache", cache, "--query", "deployment", "--budget", "100"],
+        &["resolve", "--cache", cache, "--query", "deployment", "--budget", "-1"],
+        &["resolve", "--cache", cache, "--query", "deployment", "--budget", "lots"],
+        &["resolve", "--cache", cache, "--query", "deployment", "--budget", "99999999999999999999999"],
+        &["resolve", "--cache", cache, "--query", "", "--budget", "100"],
+        &["resolve", "--cache", cache, "--query", "   ", "--budget", "100"],
+        &["resolve", "--cache", cache, "--query", "deployment", "--budget", "100", "--bogus"],
+        &["resolve", "--cache", missing, "--query", "deployment", "--budget", "100"],
+        &["resolve", "--cache", not_a_dir, "--query", "deployment", "--budget", "100"],
+        &["inspect"],
+        &["inspect", "--cache", cache],
+        &["inspect", "--cache", missing],
+        &["inspect", "--cache", not_a_dir],
+        &["build"],
+        &["build", "--sources", sources],
+        &["build", "--sources", missing, "--cache", scratch],
+        &["build", "--sources", not_a_dir, "--cache", scratch],
+        &["build", "--sources", sources, "--cache", not_a_dir],
+        &["build", "--sources", sources, "--cache", scratch],
+    ];
+
+    let mut undocumented = Vec::new();
+    for args in cases {
+        let out = runner.run(*args).unwrap();
+        if out.exit().is_none() {
+            undocumented.push(format!("{args:?}: {}", out.describe()));
+        }
+    }
+    assert!(
+        undocumented.is_empty(),
+        "exit statuses outside the documented contract:\n{}",
+        undocumented.join("\n")
     );
 }
 
+/// The README exit code table lists exactly the codes in `ExitCode`.
+#[test]
+fn readme_exit_code_table_matches_contract() {
+    let readme = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md")).unwrap();
+    let table = readme
+        .split("## CLI exit code contract")
+        .nth(1)
+        .expect("README has no exit code contract section");
+    let documented: Vec<i32> = table
+        .lines()
+        .skip_while(|l| !l.starts_with('|'))
+        .take_while(|l| l.starts_with('|'))
+        .filter_map(|l| l.split('|').nth(1)?.trim().parse().ok())
+        .collect();
+    let contract: Vec<i32> = ExitCode::ALL.iter().map(|c| c.code()).collect();
+    assert_eq!(documented, contract, "README exit code table drifted from ExitCode");
+}
+
 // --- Permission denied / IO error boundary ---
 
 /// Unreadable cache directory returns IO_ERROR or CACHE_MISSING.
@@ -222,12 +310,14 @@ fn exit_code_permission_denied() {
     #[cfg(unix)]
     {
         let out = runner.resolve(&cache, "test", 100).unwrap();
-        assert_ne!(out.exit_code, EXIT_SUCCESS, "should fail on unreadable manifest");
+        assert_ne!(out.exit(), Some(ExitCode::Success), "should fail on unreadable manifest");
         // IO_ERROR (6) for permission denied
         assert!(
-            out.exit_code == EXIT_IO_ERROR || out.exit_code == EXIT_CACHE_MISSING,
-            "expected exit code {EXIT_IO_ERROR} or {EXIT_CACHE_MISSING}, got {}",
-            out.exit_code
+            out.exit() == Some(ExitCode::IoError) || out.exit() == Some(ExitCode::CacheMissing),
+            "expected exit code {} or {}, got {}",
+            ExitCode::IoError,
+            ExitCode::CacheMissing,
+            out.describe()
         );
 
         // Restore permissions so tempdir cleanup works
@@ -259,7 +349,7 @@ fn future_version_cache_behavior() {
     // Document current behavior: the binary may or may not reject future versions.
     // If it rejects: exit_code should be CACHE_INVALID (5).
     // If it accepts: the output should still be valid JSON.
-    if out.exit_code == EXIT_SUCCESS {
+    if out.exit() == Some(ExitCode::Success) {
         // Currently loads — verify output is at least valid JSON
         let v: serde_json::Value = serde_json::from_str(out.stdout.trim())
             .expect("future version cache should produce valid JSON if loaded");
@@ -267,9 +357,10 @@ fn future_version_cache_behavior() {
     } else {
         // Version validation was added — verify it's the right error code
         assert_eq!(
-            out.exit_code, EXIT_CACHE_INVALID,
-            "future version rejection should use exit code {EXIT_CACHE_INVALID}, got {}",
-            out.exit_code
+            out.exit(), Some(ExitCode::CacheInvalid),
+            "future version rejection should use exit code {}, got {}",
+            ExitCode::CacheInvalid,
+            out.describe()
         );
     }
 }
@@ -288,7 +379,7 @@ fn future_version_inspect() {
     let cache = fixture::cache_path("future_version");
     let out = runner.inspect(&cache).unwrap();
 
-    if out.exit_code == EXIT_SUCCESS {
+    if out.exit() == Some(ExitCode::Success) {
         let v: serde_json::Value = serde_json::from_str(out.stdout.trim()).unwrap();
         assert!(v["cache_version"].is_string());
         assert_eq!(v["document_count"], 1);
@@ -0,0 +1,278 @@
+//! `CONTEXT_CACHE_ROOT` escape and path-injection tests for the MCP server.
+//!
+//! `context.resolve` and `context.inspect_cache` take a caller-supplied cache
+//! name that the server resolves under `CONTEXT_CACHE_ROOT`. Adversarial names
+//! (`../`, absolute paths, encoded and Windows separators, symlinks leaving
+//! the root, NUL bytes, very long names) must each yield a well-formed MCP
+//! error that rejects the name: invalid params (`-32602`) at the JSON-RPC
+//! layer, or a `cache_missing`/`cache_invalid` tool error (`invalid_query`
+//! also for a `cache` of the wrong type). A canary cache sits next to the
+//! root; any response carrying its cache version or documents means the
+//! server read outside the root.
+
+use context_compat::fixture;
+use context_compat::mcp_runner::McpRunner;
+use context_compat::source_tree::{SourceTree, WriteOptions};
+use jsonschema::validator_for;
+use serde_json::{json, Value};
+use std::path::Path;
+
+fn mcp(cache_root: &Path) -> Option<McpRunner> {
+    match McpRunner::from_env(cache_root) {
+        Some(Ok(runner)) => Some(runner),
+        Some(Err(e)) => panic!("failed to spawn MCP server: {e}"),
+        None => {
+            eprintln!("MCP_SERVER_BIN not set, skipping");
+            None
+        }
+    }
+}
+
+fn copy_cache(name: &str, dest: &Path) {
+    SourceTree::read(&fixture::cache_path(name))
+        .unwrap()
+        .write(dest, &WriteOptions::default())
+        .unwrap();
+}
+
+/// Strings that only appear in responses served from the canary cache.
+struct Canary {
+    markers: Vec<String>,
+}
+
+impl Canary {
+    /// The canary is a copy of the `realistic` cache, which shares no cache
+    /// version or document ids with the `minimal` cache inside the root.
+    fn plant(dest: &Path) -> Self {
+        copy_cache("realistic", dest);
+        let manifest: Value =
+            serde_json::from_str(&std::fs::read_to_string(dest.join("manifest.json")).unwrap())
+                .unwrap();
+        let mut markers = vec![manifest["cache_version"].as_str().unwrap().to_string()];
+        let inside: Value = serde_json::from_str(
+            &std::fs::read_to_string(fixture::cache_path("minimal").join("manifest.json")).unwrap(),
+        )
+        .unwrap();
+        let inside_ids: Vec<&Value> = inside["documents"]
+            .as_array()
+            .map(|d| d.iter().map(|e| &e["id"]).collect())
+            .unwrap_or_default();
+        for doc in manifest["documents"].as_array().into_iter().flatten() {
+            if !inside_ids.contains(&&doc["id"]) {
+                markers.push(doc["id"].as_str().unwrap().to_string());
+            }
+        }
+        Self { markers }
+    }
+
+    fn leaked_in(&self, response: &str) -> Option<&str> {
+        self.markers
+            .iter()
+            .find(|m| response.contains(m.as_str()))
+            .map(String::as_str)
+    }
+}
+
+/// Lay out `base/root` (the cache root) with one legitimate cache and
+/// symlinks pointing out of it, plus canaries at `base/canary` and
+/// `base/root-canary` (a sibling sharing the root's name as a string prefix).
+fn layout(base: &Path) -> (std::path::PathBuf, Canary) {
+    let root = base.join("root");
+    copy_cache("minimal", &root.join("inside"));
+    std::fs::create_dir_all(root.join("nested").join("deeper")).unwrap();
+    let canary = Canary::plant(&base.join("canary"));
+    copy_cache("realistic", &base.join("root-canary"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::symlink;
+        symlink(base.join("canary"), root.join("escape_link")).unwrap();
+        symlink("../canary", root.join("relative_escape_link")).unwrap();
+        symlink("../../../canary", root.join("nested").join("deeper").join("link")).unwrap();
+        symlink(base, root.join("parent_link")).unwrap();
+        symlink("/", root.join("fs_root_link")).unwrap();
+    }
+    (root, canary)
+}
+
+/// Cache names that must never resolve to anything.
+fn adversarial_names(base: &Path) -> Vec<String> {
+    let canary_abs = base.join("canary").to_string_lossy().into_owned();
+    let mut names: Vec<String> = [
+        // Parent traversal.
+        "..",
+        "../canary",
+        "../../canary",
+        "../root-canary",
+        "inside/../../canary",
+        "nested/deeper/../../../canary",
+        "./../canary",
+        ".",
+        "",
+        // Encoded separators and dots.
+        "..%2Fcanary",
+        "%2e%2e%2fcanary",
+        "%2E%2E/canary",
+        "..%252Fcanary",
+        "..%c0%afcanary",
+        "..\u{2215}canary",
+        "\u{ff0e}\u{ff0e}\u{ff0f}canary",
+        // Windows-style separators and paths.
+        "..\\canary",
+        "inside\\..\\..\\canary",
+        "C:\\Windows\\System32",
+        "C:canary",
+        "\\\\server\\share\\canary",
+        "\\\\?\\C:\\canary",
+        // Symlinks leaving the root.
+        "escape_link",
+        "relative_escape_link",
+        "nested/deeper/link",
+        "parent_link/canary",
+        "fs_root_link",
+        "fs_root_link/etc",
+        // NUL bytes.
+        "inside\u{0}",
+        "\u{0}",
+        "../canary\u{0}inside",
+        "inside\u{0}/../../canary",
+        // Home and environment expansion.
+        "~",
+        "~/canary",
+        "$HOME",
+        "${CONTEXT_CACHE_ROOT}/../canary",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    // Absolute paths.
+    names.push(canary_abs.clone());
+    names.push(format!("{canary_abs}/"));
+    names.push(format!("file://{canary_abs}"));
+    names.push("/".to_string());
+    names.push("/etc/passwd".to_string());
+    // Extremely long names.
+    names.push("a".repeat(256));
+    names.push("a".repeat(4096));
+    names.push("a".repeat(1 << 20));
+    names.push("../".repeat(512) + "canary");
+    names.push("inside/".repeat(1024));
+    names
+}
+
+/// Tool error codes that reject a cache name.
+const NAME_REJECTIONS: &[&str] = &["cache_missing", "cache_invalid"];
+
+/// Tool error codes that reject a `cache` argument of the wrong type: those
+/// for a bad name, or the invalid-arguments code.
+const ARGUMENT_REJECTIONS: &[&str] = &["cache_missing", "cache_invalid", "invalid_query"];
+
+/// Assert `response` is a well-formed MCP error with one of the `rejections`
+/// codes that leaked nothing from the canary.
+fn assert_contained(response: &str, canary: &Canary, rejections: &[&str], ctx: &str) {
+    if let Some(marker) = canary.leaked_in(response) {
+        panic!("{ctx}: response leaks canary data ({marker}), the server read outside the root");
+    }
+    let v: Value = serde_json::from_str(response.trim())
+        .unwrap_or_else(|e| panic!("{ctx}: response is not JSON ({e}): {response}"));
+    assert_eq!(v["jsonrpc"], "2.0", "{ctx}: {v}");
+
+    // Rejection at the JSON-RPC layer is acceptable as invalid params.
+    if let Some(error) = v.get("error") {
+        assert_eq!(error["code"], -32602, "{ctx}: JSON-RPC error is not invalid params: {v}");
+        assert!(error["message"].is_string(), "{ctx}: JSON-RPC error without message: {v}");
+        return;
+    }
+
+    let result = &v["result"];
+    assert_eq!(result["isError"], true, "{ctx}: adversarial name was accepted: {v}");
+    let text = result["content"][0]["text"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{ctx}: error result has no text content: {v}"));
+    let inner: Value = serde_json::from_str(text.trim())
+        .unwrap_or_else(|e| panic!("{ctx}: error text is not JSON ({e}): {text}"));
+    let validator = validator_for(&fixture::schema("mcp_error")).unwrap();
+    let errors: Vec<String> = validator.iter_errors(&inner).map(|e| e.to_string()).collect();
+    assert!(errors.is_empty(), "{ctx}: error violates mcp_error schema: {errors:?}\n{inner}");
+    let code = inner["error"]["code"].as_str().unwrap();
+    assert!(
+        rejections.contains(&code),
+        "{ctx}: error code {code} is not one of {rejections:?}: {inner}"
+    );
+}
+
+fn short(name: &str) -> String {
+    if name.len() > 64 {
+        let head: String = name.chars().take(32).collect();
+        format!("{head:?}... ({} bytes)", name.len())
+    } else {
+        format!("{name:?}")
+    }
+}
+
+/// Every adversarial name fails cleanly through both cache-taking tools.
+#[test]
+fn adversarial_cache_names_stay_inside_root() {
+    let base = tempfile::tempdir().unwrap();
+    let (root, canary) = layout(base.path());
+    let mut runner = match mcp(&root) {
+        Some(r) => r,
+        None => return,
+    };
+    runner.initialize().unwrap();
+
+    for name in adversarial_names(base.path()) {
+        let ctx = format!("resolve {}", short(&name));
+        let response = runner
+            .call_tool(
+                "context.resolve",
+                json!({ "cache": name, "query": "deployment", "budget": 4000 }),
+            )
+            .unwrap();
+        assert!(!response.is_empty(), "{ctx}: server closed stdout");
+        assert_contained(&response, &canary, NAME_REJECTIONS, &ctx);
+
+        let ctx = format!("inspect_cache {}", short(&name));
+        let response = runner
+            .call_tool("context.inspect_cache", json!({ "cache": name }))
+            .unwrap();
+        assert!(!response.is_empty(), "{ctx}: server closed stdout");
+        assert_contained(&response, &canary, NAME_REJECTIONS, &ctx);
+    }
+
+    // The legitimate cache still resolves afterwards.
+    let response = runner
+        .call_tool("context.inspect_cache", json!({ "cache": "inside" }))
+        .unwrap();
+    let v: Value = serde_json::from_str(response.trim()).unwrap();
+    assert_ne!(v["result"]["isError"], true, "inside cache rejected after attack: {v}");
+}
+
+/// `cache` values of the wrong JSON type are rejected, not coerced into paths.
+#[test]
+fn non_string_cache_names_rejected() {
+    let base = tempfile::tempdir().unwrap();
+    let (root, canary) = layout(base.path());
+    let mut runner = match mcp(&root) {
+        Some(r) => r,
+        None => return,
+    };
+    runner.initialize().unwrap();
+
+    for cache in [
+        json!(null),
+        json!(0),
+        json!(true),
+        json!([".."]),
+        json!(["..", "canary"]),
+        json!({ "path": "../canary" }),
+    ] {
+        let ctx = format!("resolve cache={cache}");
+        let response = runner
+            .call_tool(
+                "context.resolve",
+                json!({ "cache": cache, "query": "deployment", "budget": 4000 }),
+            )
+            .unwrap();
+        assert_contained(&response, &canary, ARGUMENT_REJECTIONS, &ctx);
+    }
+}
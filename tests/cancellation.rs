@@ -0,0 +1,253 @@
+//! Progress notifications and request cancellation over MCP.
+//!
+//! `tools/call` requests carry `_meta.progressToken`; any
+//! `notifications/progress` the server emits must reference that token with a
+//! strictly increasing `progress` that never exceeds `total`. Emitting none is
+//! allowed. After `notifications/cancelled` for an in-flight request the
+//! server either suppresses the response or answers exactly once, and keeps
+//! serving later requests.
+//!
+//! Resolves run against a generated corpus when `CONTEXT_CLI_BIN` is set so
+//! they take long enough to be in flight; otherwise the fixture caches are
+//! used.
+
+use context_compat::cli_runner::CliRunner;
+use context_compat::corpus::{Corpus, CorpusSpec};
+use context_compat::fixture;
+use context_compat::mcp_runner::McpRunner;
+use context_compat::source_tree::{SourceTree, WriteOptions};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+
+const SEED: u64 = 20_260_401;
+const LARGE_DOCS: usize = 2_000;
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// How long the server must stay silent before a late message is ruled out.
+const QUIET: Duration = Duration::from_millis(500);
+
+/// An initialized server over a fresh [`cache_root`] under `dir`. Checks for
+/// the server before building anything, since the corpus build is slow.
+fn mcp(dir: &Path) -> Option<McpRunner> {
+    if std::env::var_os("MCP_SERVER_BIN").is_none() {
+        eprintln!("MCP_SERVER_BIN not set, skipping");
+        return None;
+    }
+    match McpRunner::from_env(&cache_root(dir)) {
+        Some(Ok(mut runner)) => {
+            runner.initialize().unwrap();
+            Some(runner)
+        }
+        Some(Err(e)) => panic!("failed to spawn MCP server: {e}"),
+        None => unreachable!("MCP_SERVER_BIN checked above"),
+    }
+}
+
+/// Lay out a cache root holding `large`: a generated corpus built with the
+/// CLI if available, else a copy of the `realistic` fixture cache.
+fn cache_root(dir: &Path) -> std::path::PathBuf {
+    let root = dir.join("root");
+    let cache = root.join("large");
+    match CliRunner::from_env() {
+        Some(cli) => {
+            let sources = dir.join("sources");
+            Corpus::new(CorpusSpec::new(SEED, LARGE_DOCS))
+                .write(&sources)
+                .unwrap();
+            std::fs::create_dir_all(&root).unwrap();
+            let out = cli.build(&sources, &cache, false).unwrap();
+            assert_eq!(out.exit_code, 0, "build failed: {}", out.describe());
+        }
+        None => SourceTree::read(&fixture::cache_path("realistic"))
+            .unwrap()
+            .write(&cache, &WriteOptions::default())
+            .unwrap(),
+    }
+    root
+}
+
+fn resolve_params(progress_token: Option<Value>) -> Value {
+    let mut params = json!({
+        "name": "context.resolve",
+        "arguments": { "cache": "large", "query": "deployment security", "budget": 1_000_000 }
+    });
+    if let Some(token) = progress_token {
+        params["_meta"] = json!({ "progressToken": token });
+    }
+    params
+}
+
+/// A successful, non-error `tools/call` response.
+fn assert_tool_ok(response: &Value, ctx: &str) {
+    assert_eq!(response["jsonrpc"], "2.0", "{ctx}: {response}");
+    assert!(response.get("error").is_none(), "{ctx}: {response}");
+    assert_ne!(response["result"]["isError"], true, "{ctx}: {response}");
+}
+
+/// The server answers a `ping` promptly.
+fn assert_healthy(runner: &mut McpRunner, ctx: &str) {
+    let id = runner.request_async("ping", json!({})).unwrap();
+    let response = runner
+        .wait_for(id, TIMEOUT)
+        .unwrap()
+        .unwrap_or_else(|| panic!("{ctx}: no ping response within {TIMEOUT:?}"));
+    assert!(response.get("result").is_some(), "{ctx}: ping failed: {response}");
+}
+
+/// Every collected notification is well-formed; progress ones match `token`.
+fn check_notifications(notifications: &[Value], token: &Value, ctx: &str) {
+    let mut last: Option<f64> = None;
+    for n in notifications {
+        assert_eq!(n["jsonrpc"], "2.0", "{ctx}: {n}");
+        assert!(n["method"].is_string(), "{ctx}: notification without method: {n}");
+        if n["method"] != "notifications/progress" {
+            continue;
+        }
+        let params = &n["params"];
+        assert_eq!(&params["progressToken"], token, "{ctx}: progress for another token: {n}");
+        let progress = params["progress"]
+            .as_f64()
+            .unwrap_or_else(|| panic!("{ctx}: progress is not a number: {n}"));
+        if let Some(prev) = last {
+            assert!(progress > prev, "{ctx}: progress went from {prev} to {progress}");
+        }
+        if let Some(total) = params.get("total") {
+            let total = total
+                .as_f64()
+                .unwrap_or_else(|| panic!("{ctx}: total is not a number: {n}"));
+            assert!(progress <= total, "{ctx}: progress {progress} exceeds total {total}");
+        }
+        last = Some(progress);
+    }
+}
+
+/// Progress notifications, if any, are well-formed for string and integer tokens.
+#[test]
+fn progress_notifications_well_formed() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut runner = match mcp(dir.path()) {
+        Some(r) => r,
+        None => return,
+    };
+
+    for token in [json!("compat-progress"), json!(7)] {
+        let ctx = format!("progressToken {token}");
+        let id = runner
+            .request_async("tools/call", resolve_params(Some(token.clone())))
+            .unwrap();
+        let response = runner
+            .wait_for(id, TIMEOUT)
+            .unwrap()
+            .unwrap_or_else(|| panic!("{ctx}: no response within {TIMEOUT:?}"));
+        assert_tool_ok(&response, &ctx);
+        // Pick up progress sent just before or after the response.
+        runner.collect(QUIET).unwrap();
+        let notifications = runner.take_notifications();
+        eprintln!("{ctx}: {} notifications", notifications.len());
+        check_notifications(&notifications, &token, &ctx);
+        assert!(runner.take_responses(id).is_empty(), "{ctx}: answered more than once");
+    }
+}
+
+/// A cancelled in-flight request is answered at most once and the server stays healthy.
+#[test]
+fn cancelled_request_answered_at_most_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut runner = match mcp(dir.path()) {
+        Some(r) => r,
+        None => return,
+    };
+
+    let token = json!("compat-cancel");
+    let id = runner
+        .request_async("tools/call", resolve_params(Some(token.clone())))
+        .unwrap();
+    runner
+        .notify(
+            "notifications/cancelled",
+            json!({ "requestId": id, "reason": "compat harness cancellation" }),
+        )
+        .unwrap();
+
+    assert_healthy(&mut runner, "after cancellation");
+    runner.collect(QUIET).unwrap();
+    let responses = runner.take_responses(id);
+    assert!(responses.len() <= 1, "cancelled request answered {} times", responses.len());
+    if let Some(response) = responses.first() {
+        // Either the completed result or an error; both must be well-formed.
+        assert_eq!(response["jsonrpc"], "2.0", "{response}");
+        assert!(
+            response.get("result").is_some() != response.get("error").is_some(),
+            "response must carry exactly one of result and error: {response}"
+        );
+    }
+    check_notifications(&runner.take_notifications(), &token, "cancelled request");
+
+    let id = runner.request_async("tools/call", resolve_params(None)).unwrap();
+    let response = runner.wait_for(id, TIMEOUT).unwrap().expect("no response after cancellation");
+    assert_tool_ok(&response, "resolve after cancellation");
+}
+
+/// Cancelling one of several pipelined requests leaves the others answered exactly once.
+#[test]
+fn cancellation_only_affects_its_request() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut runner = match mcp(dir.path()) {
+        Some(r) => r,
+        None => return,
+    };
+
+    let ids: Vec<u64> = (0..5)
+        .map(|_| runner.request_async("tools/call", resolve_params(None)).unwrap())
+        .collect();
+    let cancelled = ids[2];
+    runner
+        .notify("notifications/cancelled", json!({ "requestId": cancelled }))
+        .unwrap();
+
+    for &id in ids.iter().filter(|&&id| id != cancelled) {
+        let response = runner
+            .wait_for(id, TIMEOUT)
+            .unwrap()
+            .unwrap_or_else(|| panic!("request {id}: no response within {TIMEOUT:?}"));
+        assert_tool_ok(&response, &format!("request {id}"));
+    }
+    runner.collect(QUIET).unwrap();
+    for &id in &ids {
+        let extra = runner.take_responses(id);
+        let allowed = usize::from(id == cancelled);
+        assert!(extra.len() <= allowed, "request {id} answered too often: {extra:?}");
+    }
+    assert_healthy(&mut runner, "after pipelined cancellation");
+}
+
+/// Cancelling unknown or already-answered requests is ignored without a response.
+#[test]
+fn stale_cancellations_ignored() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut runner = match mcp(dir.path()) {
+        Some(r) => r,
+        None => return,
+    };
+
+    let id = runner.request_async("tools/call", resolve_params(None)).unwrap();
+    let response = runner.wait_for(id, TIMEOUT).unwrap().expect("no resolve response");
+    assert_tool_ok(&response, "resolve");
+
+    for params in [
+        json!({ "requestId": id }),
+        json!({ "requestId": 999_999 }),
+        json!({ "requestId": "never-sent" }),
+        json!({}),
+    ] {
+        runner.notify("notifications/cancelled", params).unwrap();
+    }
+
+    assert_healthy(&mut runner, "after stale cancellations");
+    runner.collect(QUIET).unwrap();
+    let notifications = runner.take_notifications();
+    check_notifications(&notifications, &Value::Null, "stale cancellations");
+    assert!(runner.take_responses(id).is_empty(), "completed request answered again");
+    let answered = runner.take_all();
+    assert!(answered.is_empty(), "stale cancellations were answered: {answered:?}");
+}
@@ -0,0 +1,187 @@
+//! Capability honesty: what `initialize` advertises is what the server serves.
+//!
+//! If `capabilities.resources` is present, `resources/list`, `resources/read`
+//! and `resources/templates/list` are exercised against the fixture caches and
+//! validated against `schemas/mcp_resource*.schema.json`. If
+//! `capabilities.prompts` is present, `prompts/list` and `prompts/get` are
+//! exercised the same way. Methods of a capability that is not advertised
+//! must answer JSON-RPC method-not-found (-32601).
+
+use context_compat::fixture;
+use context_compat::mcp_runner::McpRunner;
+use jsonschema::validator_for;
+use serde_json::{json, Value};
+
+const METHOD_NOT_FOUND: i64 = -32601;
+
+const RESOURCE_METHODS: &[&str] = &["resources/list", "resources/read", "resources/templates/list"];
+const PROMPT_METHODS: &[&str] = &["prompts/list", "prompts/get"];
+
+fn mcp() -> Option<McpRunner> {
+    let cache_root = fixture::v0_root().join("caches");
+    match McpRunner::from_env(&cache_root) {
+        Some(Ok(runner)) => Some(runner),
+        Some(Err(e)) => panic!("failed to spawn MCP server: {e}"),
+        None => {
+            eprintln!("MCP_SERVER_BIN not set, skipping");
+            None
+        }
+    }
+}
+
+/// Spawn, initialize and return the runner with the advertised capabilities.
+fn initialized() -> Option<(McpRunner, Value)> {
+    let mut runner = mcp()?;
+    let response = runner.initialize().unwrap();
+    let v: Value = serde_json::from_str(response.trim()).unwrap();
+    let capabilities = v["result"]["capabilities"].clone();
+    assert!(capabilities.is_object(), "initialize without capabilities: {v}");
+    Some((runner, capabilities))
+}
+
+fn request(runner: &mut McpRunner, method: &str, params: Value) -> Value {
+    let response = runner.request(method, params).unwrap();
+    assert!(!response.is_empty(), "{method}: server closed stdout");
+    let v: Value = serde_json::from_str(response.trim())
+        .unwrap_or_else(|e| panic!("{method}: response is not JSON ({e}): {response}"));
+    assert_eq!(v["jsonrpc"], "2.0", "{method}: {v}");
+    v
+}
+
+/// The `result` of a successful response, validated against `schema`.
+fn result(runner: &mut McpRunner, method: &str, params: Value, schema: &str) -> Value {
+    let v = request(runner, method, params);
+    assert!(v.get("error").is_none(), "{method}: advertised but failed: {v}");
+    let result = v["result"].clone();
+    let validator = validator_for(&fixture::schema(schema)).unwrap();
+    let errors: Vec<String> = validator.iter_errors(&result).map(|e| e.to_string()).collect();
+    assert!(errors.is_empty(), "{method}: result violates {schema} schema: {errors:?}\n{result}");
+    result
+}
+
+/// A well-formed JSON-RPC error with an integer code; returns the code.
+fn error_code(v: &Value, ctx: &str) -> i64 {
+    let error = v
+        .get("error")
+        .unwrap_or_else(|| panic!("{ctx}: expected a JSON-RPC error, got {v}"));
+    assert!(error["message"].is_string(), "{ctx}: error without message: {v}");
+    error["code"]
+        .as_i64()
+        .unwrap_or_else(|| panic!("{ctx}: error without integer code: {v}"))
+}
+
+fn assert_method_not_found(runner: &mut McpRunner, methods: &[&str], capability: &str) {
+    for method in methods {
+        let v = request(runner, method, json!({}));
+        let ctx = format!("{method} with `{capability}` not advertised");
+        assert_eq!(error_code(&v, &ctx), METHOD_NOT_FOUND, "{ctx}: {v}");
+    }
+}
+
+/// A plausible value for a prompt argument, drawn from the fixtures.
+fn argument_value(name: &str) -> &'static str {
+    let name = name.to_lowercase();
+    if name.contains("cache") {
+        "minimal"
+    } else if name.contains("budget") {
+        "4000"
+    } else {
+        "deployment"
+    }
+}
+
+#[test]
+fn resources_match_capability() {
+    let (mut runner, capabilities) = match initialized() {
+        Some(r) => r,
+        None => return,
+    };
+    if capabilities.get("resources").is_none() {
+        assert_method_not_found(&mut runner, RESOURCE_METHODS, "resources");
+        return;
+    }
+    assert!(capabilities["resources"].is_object(), "resources capability: {capabilities}");
+
+    let list = result(&mut runner, "resources/list", json!({}), "mcp_resources_list");
+    let resources = list["resources"].as_array().unwrap();
+    for resource in resources {
+        let uri = resource["uri"].as_str().unwrap();
+        let read = result(
+            &mut runner,
+            "resources/read",
+            json!({ "uri": uri }),
+            "mcp_resources_read",
+        );
+        for content in read["contents"].as_array().unwrap() {
+            assert!(content["uri"].is_string(), "{uri}: content without uri: {content}");
+        }
+    }
+
+    result(
+        &mut runner,
+        "resources/templates/list",
+        json!({}),
+        "mcp_resource_templates_list",
+    );
+
+    let v = request(
+        &mut runner,
+        "resources/read",
+        json!({ "uri": "context://no-such-resource/../../etc/passwd" }),
+    );
+    let code = error_code(&v, "resources/read of an unknown uri");
+    assert_ne!(code, METHOD_NOT_FOUND, "resources/read advertised but not found: {v}");
+}
+
+#[test]
+fn prompts_match_capability() {
+    let (mut runner, capabilities) = match initialized() {
+        Some(r) => r,
+        None => return,
+    };
+    if capabilities.get("prompts").is_none() {
+        assert_method_not_found(&mut runner, PROMPT_METHODS, "prompts");
+        return;
+    }
+    assert!(capabilities["prompts"].is_object(), "prompts capability: {capabilities}");
+
+    let list = result(&mut runner, "prompts/list", json!({}), "mcp_prompts_list");
+    for prompt in list["prompts"].as_array().unwrap() {
+        let name = prompt["name"].as_str().unwrap();
+        let mut arguments = serde_json::Map::new();
+        for arg in prompt["arguments"].as_array().into_iter().flatten() {
+            let arg_name = arg["name"].as_str().unwrap();
+            arguments.insert(arg_name.to_string(), json!(argument_value(arg_name)));
+        }
+        result(
+            &mut runner,
+            "prompts/get",
+            json!({ "name": name, "arguments": arguments }),
+            "mcp_prompts_get",
+        );
+    }
+
+    let v = request(
+        &mut runner,
+        "prompts/get",
+        json!({ "name": "no.such.prompt", "arguments": {} }),
+    );
+    let code = error_code(&v, "prompts/get of an unknown prompt");
+    assert_ne!(code, METHOD_NOT_FOUND, "prompts/get advertised but not found: {v}");
+}
+
+/// Every advertised capability is an object. Ones the harness has no checks
+/// for are reported, not rejected.
+#[test]
+fn capabilities_are_objects() {
+    let (_, capabilities) = match initialized() {
+        Some(r) => r,
+        None => return,
+    };
+    for (name, value) in capabilities.as_object().unwrap() {
+        assert!(value.is_object(), "capability {name:?} is not an object: {value}");
+        if !["tools", "resources", "prompts", "logging"].contains(&name.as_str()) {
+            eprintln!("capability {name:?} advertised, not checked by this harness");
+        }
+    }
+}
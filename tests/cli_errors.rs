@@ -0,0 +1,203 @@
+//! CLI stderr error contract: every non-zero exit prints exactly one JSON line
+//! `{"error": {"code", "message"}}` to stderr, valid against
+//! `schemas/cli_error.schema.json`, whose `code` matches the exit code.
+//!
+//! One golden per failure class lives in `fixtures/v0/expected/cli_error_*.json`,
+//! recorded with `CONTEXT_CLI_ERRORS_UPDATE=1` (or `make fixtures-errors`).
+//! Goldens freeze both `code` and `message`. Invocations run inside a scratch
+//! directory with relative paths, and any absolute scratch path in a message is
+//! replaced by `$SCRATCH`, so messages do not depend on where the test runs.
+
+use context_compat::cli_runner::{CliEnv, CliRunner};
+use context_compat::exit_code::ExitCode;
+use context_compat::fixture;
+use context_compat::source_tree::{SourceTree, WriteOptions};
+use jsonschema::validator_for;
+use serde_json::Value;
+use std::path::Path;
+
+fn cli() -> Option<CliRunner> {
+    CliRunner::from_env()
+}
+
+/// Lay out the scratch directory every invocation runs in:
+/// `minimal/` (valid cache), `corrupt/` (bad manifest), `sources/` (documents)
+/// and `blocker` (a plain file standing where a directory is needed).
+fn scratch(dir: &Path) -> CliEnv {
+    let copy = |from: &Path, to: &str| {
+        SourceTree::read(from)
+            .unwrap()
+            .write(&dir.join(to), &WriteOptions::default())
+            .unwrap();
+    };
+    copy(&fixture::cache_path("minimal"), "minimal");
+    copy(&fixture::documents_path("minimal"), "sources");
+    std::fs::create_dir(dir.join("corrupt")).unwrap();
+    std::fs::write(dir.join("corrupt").join("manifest.json"), "not valid json").unwrap();
+    std::fs::write(dir.join("blocker"), "not a directory").unwrap();
+    CliEnv {
+        current_dir: Some(dir.to_path_buf()),
+        ..CliEnv::default()
+    }
+}
+
+/// One representative invocation per failure class, named after its golden.
+const GOLDEN_CASES: &[(&str, ExitCode, &[&str])] = &[
+    (
+        "cli_error_usage",
+        ExitCode::Usage,
+        &["resolve", "--cache", "minimal", "--query", "deployment"],
+    ),
+    (
+        "cli_error_invalid_query",
+        ExitCode::InvalidQuery,
+        &["resolve", "--cache", "minimal", "--query", "deployment\u{7}security", "--budget", "4000"],
+    ),
+    (
+        "cli_error_invalid_budget",
+        ExitCode::InvalidBudget,
+        &["resolve", "--cache", "minimal", "--query", "deployment", "--budget", "lots"],
+    ),
+    (
+        "cli_error_cache_missing",
+        ExitCode::CacheMissing,
+        &["resolve", "--cache", "missing", "--query", "deployment", "--budget", "4000"],
+    ),
+    (
+        "cli_error_cache_invalid",
+        ExitCode::CacheInvalid,
+        &["resolve", "--cache", "corrupt", "--query", "deployment", "--budget", "4000"],
+    ),
+    (
+        "cli_error_io_error",
+        ExitCode::IoError,
+        &["build", "--sources", "sources", "--cache", "blocker/cache"],
+    ),
+];
+
+/// Further failing invocations checked against the schema only.
+const OTHER_FAILURES: &[&[&str]] = &[
+    &[],
+    &["frobnicate"],
+    &["resolve"],
+    &["resolve", "--cache", "minimal", "--query", "deployment", "--budget", "4000", "--bogus"],
+    &["resolve", "--cache", "minimal", "--query", "deployment", "--budget", "-1"],
+    &["resolve", "--cache", "minimal", "--query", "deployment", "--budget", "99999999999999999999999"],
+    &["resolve", "--cache", "blocker", "--query", "deployment", "--budget", "4000"],
+    &["inspect"],
+    &["inspect", "--cache", "missing"],
+    &["inspect", "--cache", "corrupt"],
+    &["inspect", "--cache", "blocker"],
+    &["build", "--sources", "sources"],
+    &["build", "--sources", "missing", "--cache", "out"],
+    &["build", "--sources", "blocker", "--cache", "out"],
+];
+
+/// `error.code` and `error.message` of one stderr error line.
+fn code_and_message(what: &str, line: &str) -> (String, String) {
+    let v: Value = serde_json::from_str(line.trim())
+        .unwrap_or_else(|e| panic!("{what}: not JSON ({e}): {line}"));
+    let field = |name: &str| {
+        v["error"][name]
+            .as_str()
+            .unwrap_or_else(|| panic!("{what}: error.{name} is not a string: {line}"))
+            .to_string()
+    };
+    (field("code"), field("message"))
+}
+
+/// `line` with the scratch directory's absolute path replaced by `$SCRATCH`.
+fn normalize(line: &str, dir: &Path) -> String {
+    let canonical = dir.canonicalize().unwrap();
+    [canonical.as_path(), dir]
+        .iter()
+        .fold(line.trim().to_string(), |line, p| line.replace(p.to_str().unwrap(), "$SCRATCH"))
+}
+
+/// Each failure class prints its golden's error code and message, and nothing
+/// on stdout.
+#[test]
+fn stderr_matches_goldens() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let env = scratch(dir.path());
+    let update = std::env::var("CONTEXT_CLI_ERRORS_UPDATE").as_deref() == Ok("1");
+
+    for (golden, exit, args) in GOLDEN_CASES {
+        let out = runner.run_in(&env, *args).unwrap();
+        assert_eq!(out.exit(), Some(*exit), "{golden} {args:?}: {}", out.describe());
+        assert!(out.stdout.is_empty(), "{golden}: failing run wrote stdout: {}", out.stdout);
+        let line = normalize(&out.stderr, dir.path());
+        let (code, message) = code_and_message(golden, &line);
+        assert!(!message.is_empty(), "{golden}: empty error message");
+
+        let path = fixture::v0_root().join("expected").join(format!("{golden}.json"));
+        if update {
+            std::fs::write(&path, line + "\n").unwrap();
+            eprintln!("updated {}", path.display());
+            continue;
+        }
+        fixture::require_recorded(&path, "fixtures-errors");
+        let (want_code, want_message) =
+            code_and_message(&format!("{golden} golden"), &fixture::expected(golden));
+        assert_eq!(code, want_code, "{golden}: error code does not match golden");
+        assert_eq!(message, want_message, "{golden}: error message does not match golden");
+    }
+}
+
+/// Every non-zero exit prints one schema-valid error line whose code matches the exit code.
+#[test]
+fn every_failure_prints_contract_error() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let env = scratch(dir.path());
+    let validator = validator_for(&fixture::schema("cli_error")).unwrap();
+
+    let invocations = GOLDEN_CASES.iter().map(|(_, _, args)| *args).chain(OTHER_FAILURES.iter().copied());
+    for args in invocations {
+        let out = runner.run_in(&env, args).unwrap();
+        let exit = out
+            .exit()
+            .unwrap_or_else(|| panic!("{args:?}: exit outside the contract: {}", out.describe()));
+        if exit == ExitCode::Success {
+            continue;
+        }
+        let line = out.stderr.trim_end_matches('\n');
+        assert!(!line.is_empty() && !line.contains('\n'), "{args:?}: stderr is not one line: {:?}", out.stderr);
+        let v: Value = serde_json::from_str(line)
+            .unwrap_or_else(|e| panic!("{args:?}: stderr is not JSON ({e}): {line}"));
+        let errors: Vec<String> = validator.iter_errors(&v).map(|e| e.to_string()).collect();
+        assert!(errors.is_empty(), "{args:?}: stderr violates cli_error schema: {errors:?}\n{line}");
+        assert_eq!(
+            v["error"]["code"].as_str(),
+            exit.cli_error_code(),
+            "{args:?}: error code does not match exit code {exit}"
+        );
+    }
+}
+
+/// The schema's code enum is exactly the failing `ExitCode`s.
+#[test]
+fn schema_codes_match_exit_codes() {
+    let schema = fixture::schema("cli_error");
+    let enumerated: Vec<&str> = schema["properties"]["error"]["properties"]["code"]["enum"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c.as_str().unwrap())
+        .collect();
+    let contract: Vec<&str> = ExitCode::ALL.iter().filter_map(|c| c.cli_error_code()).collect();
+    assert_eq!(enumerated, contract);
+}
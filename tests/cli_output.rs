@@ -0,0 +1,102 @@
+//! Harness self-tests for `CliOutput` termination status and resource usage.
+//!
+//! These run stub shell scripts in place of the `context` binary, so they
+//! need no env vars and run everywhere `sh` exists (unix only).
+#![cfg(unix)]
+
+use context_compat::cli_runner::{CliRunner, KillOutcome, Termination};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::time::Duration;
+
+/// A runner for an executable script with the given body.
+fn stub(dir: &Path, body: &str) -> CliRunner {
+    let path = dir.join("context");
+    std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    CliRunner::new(path)
+}
+
+#[test]
+fn exit_code_is_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let runner = stub(dir.path(), "echo out; echo err >&2; exit 4");
+    let out = runner.inspect(Path::new("unused")).unwrap();
+
+    assert_eq!(out.exit_code, 4);
+    assert_eq!(out.termination, Termination::Exited(4));
+    assert_eq!(out.stdout, "out\n");
+    assert_eq!(out.stderr, "err\n");
+    assert!(out.describe().starts_with("exit code 4 after "), "{}", out.describe());
+}
+
+#[test]
+fn signal_death_is_distinguished_from_exit() {
+    let dir = tempfile::tempdir().unwrap();
+    let runner = stub(dir.path(), "kill -ABRT $$");
+    let out = runner.inspect(Path::new("unused")).unwrap();
+
+    assert_eq!(out.exit_code, -1);
+    match out.termination {
+        Termination::Signaled { signal, .. } => assert_eq!(signal, libc::SIGABRT),
+        other => panic!("expected a signal death, got {other}"),
+    }
+    assert!(out.describe().contains("(SIGABRT"), "{}", out.describe());
+}
+
+#[test]
+fn duration_and_usage_are_recorded() {
+    let dir = tempfile::tempdir().unwrap();
+    // Busy loop so user time is measurable.
+    let runner = stub(dir.path(), "i=0; while [ $i -lt 20000 ]; do i=$((i+1)); done");
+    let out = runner.inspect(Path::new("unused")).unwrap();
+
+    assert_eq!(out.termination, Termination::Exited(0));
+    assert!(!out.duration.is_zero());
+    assert!(!out.usage.cpu().is_zero(), "no CPU time recorded: {}", out.describe());
+    assert!(out.usage.max_rss_kb > 0, "no peak RSS recorded: {}", out.describe());
+    assert!(out.usage.cpu() <= out.duration * 2, "{}", out.describe());
+}
+
+#[test]
+fn large_output_does_not_block() {
+    let dir = tempfile::tempdir().unwrap();
+    // Well past a pipe buffer on both streams at once.
+    let runner = stub(
+        dir.path(),
+        "head -c 1048576 /dev/zero | tr '\\0' a; head -c 1048576 /dev/zero | tr '\\0' b >&2",
+    );
+    let out = runner.inspect(Path::new("unused")).unwrap();
+
+    assert_eq!(out.termination, Termination::Exited(0), "{}", out.termination);
+    assert_eq!(out.stdout.len(), 1 << 20);
+    assert_eq!(out.stderr.len(), 1 << 20);
+}
+
+#[test]
+fn build_killed_by_us_is_reported_as_killed() {
+    let dir = tempfile::tempdir().unwrap();
+    let runner = stub(dir.path(), "exec sleep 60");
+    let outcome = runner
+        .build_and_kill(Path::new("unused"), Path::new("unused"), true, Duration::from_millis(50))
+        .unwrap();
+
+    assert!(matches!(outcome, KillOutcome::Killed));
+}
+
+#[test]
+fn build_crashing_before_the_kill_is_reported_as_finished() {
+    let dir = tempfile::tempdir().unwrap();
+    let runner = stub(dir.path(), "kill -SEGV $$");
+    let outcome = runner
+        .build_and_kill(Path::new("unused"), Path::new("unused"), true, Duration::from_millis(500))
+        .unwrap();
+
+    match outcome {
+        KillOutcome::Finished(out) => match out.termination {
+            Termination::Signaled { signal, .. } => assert_eq!(signal, libc::SIGSEGV),
+            other => panic!("expected a signal death, got {other}"),
+        },
+        KillOutcome::Killed => panic!("a build that crashed by itself was reported as killed"),
+    }
+}
@@ -0,0 +1,241 @@
+//! Concurrency stress tests: many CLI processes against one cache at once.
+//!
+//! Readers must only ever observe a complete cache — either the build that was
+//! there before a `--force` rebuild or the one that replaced it, never a mix.
+
+use context_compat::cache_check::{self, CacheSummary};
+use context_compat::cli_runner::CliRunner;
+use context_compat::exit_code::ExitCode;
+use context_compat::fixture;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Barrier, Mutex};
+
+fn cli() -> Option<CliRunner> {
+    CliRunner::from_env()
+}
+
+const READERS: usize = 48;
+const REBUILDS: usize = 12;
+const RACE_ROUNDS: usize = 8;
+
+/// Stdout of `resolve` and `inspect` for one complete cache build.
+#[derive(Debug, Clone, PartialEq)]
+struct Observed {
+    resolve: String,
+    inspect: String,
+}
+
+fn observe(runner: &CliRunner, cache: &Path) -> Observed {
+    let r = runner.resolve(cache, "deployment", 4000).unwrap();
+    assert_eq!(r.exit_code, 0, "reference resolve failed: {}", r.describe());
+    let i = runner.inspect(cache).unwrap();
+    assert_eq!(i.exit_code, 0, "reference inspect failed: {}", i.describe());
+    Observed {
+        resolve: r.stdout,
+        inspect: i.stdout,
+    }
+}
+
+/// Build `sources` into a fresh directory and return what readers should see for it.
+fn reference(runner: &CliRunner, sources: &Path, cache: &Path) -> (Observed, CacheSummary) {
+    let out = runner.build(sources, cache, false).unwrap();
+    assert_eq!(out.exit_code, 0, "reference build failed: {}", out.describe());
+    let summary = cache_check::check_integrity(cache)
+        .unwrap_or_else(|e| panic!("reference cache failed integrity check: {e}"));
+    (observe(runner, cache), summary)
+}
+
+/// Dozens of parallel resolve and inspect processes against one cache all see
+/// exactly the output of a sequential run.
+#[test]
+fn parallel_readers_match_sequential() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let cache = fixture::cache_path("realistic");
+    let queries = ["basic", "multi_term", "tight_budget", "zero_budget", "empty_query"];
+
+    let expected_resolve: Vec<String> = queries
+        .iter()
+        .map(|name| {
+            let q = fixture::query(name);
+            let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
+            assert_eq!(out.exit_code, 0, "sequential resolve {name} failed: {}", out.describe());
+            out.stdout
+        })
+        .collect();
+    let expected_inspect = runner.inspect(&cache).unwrap().stdout;
+
+    let barrier = Barrier::new(READERS);
+    std::thread::scope(|s| {
+        for n in 0..READERS {
+            let (runner, cache, barrier) = (&runner, &cache, &barrier);
+            let (queries, expected_resolve, expected_inspect) =
+                (&queries, &expected_resolve, &expected_inspect);
+            s.spawn(move || {
+                barrier.wait();
+                if n % 3 == 0 {
+                    let out = runner.inspect(cache).unwrap();
+                    assert_eq!(out.exit_code, 0, "parallel inspect #{n} failed: {}", out.describe());
+                    assert_eq!(&out.stdout, expected_inspect, "parallel inspect #{n} differs");
+                } else {
+                    let k = n % queries.len();
+                    let q = fixture::query(queries[k]);
+                    let out = runner.resolve(cache, &q.query, q.budget).unwrap();
+                    assert_eq!(
+                        out.exit_code, 0,
+                        "parallel resolve #{n} ({}) failed: {}",
+                        queries[k], out.describe()
+                    );
+                    assert_eq!(
+                        out.stdout, expected_resolve[k],
+                        "parallel resolve #{n} ({}) differs from sequential run",
+                        queries[k]
+                    );
+                }
+            });
+        }
+    });
+}
+
+/// `build --force` replaces a cache while readers are active. Every reader must
+/// see either the complete old cache or the complete new one.
+#[test]
+fn force_rebuild_while_reading() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let sources_a = fixture::documents_path("minimal");
+    let sources_b = fixture::documents_path("realistic");
+    let (seen_a, summary_a) = reference(&runner, &sources_a, &dir.path().join("ref-a"));
+    let (seen_b, summary_b) = reference(&runner, &sources_b, &dir.path().join("ref-b"));
+
+    let cache = dir.path().join("live");
+    let out = runner.build(&sources_a, &cache, false).unwrap();
+    assert_eq!(out.exit_code, 0, "initial build failed: {}", out.describe());
+
+    let done = AtomicBool::new(false);
+    let violations = Mutex::new(Vec::new());
+
+    std::thread::scope(|s| {
+        for n in 0..READERS / 2 {
+            let (runner, cache, done, violations) = (&runner, &cache, &done, &violations);
+            let (seen_a, seen_b) = (&seen_a, &seen_b);
+            s.spawn(move || {
+                while !done.load(Ordering::SeqCst) {
+                    let (out, a, b) = if n % 2 == 0 {
+                        let out = runner.resolve(cache, "deployment", 4000).unwrap();
+                        (out, &seen_a.resolve, &seen_b.resolve)
+                    } else {
+                        let out = runner.inspect(cache).unwrap();
+                        (out, &seen_a.inspect, &seen_b.inspect)
+                    };
+                    if out.exit_code != 0 || (&out.stdout != a && &out.stdout != b) {
+                        violations.lock().unwrap().push(format!(
+                            "reader #{n}: {}, stdout {:?}",
+                            out.describe(),
+                            out.stdout
+                        ));
+                    }
+                }
+            });
+        }
+
+        for i in 0..REBUILDS {
+            let sources = if i % 2 == 0 { &sources_b } else { &sources_a };
+            let out = runner.build(sources, &cache, true).unwrap();
+            assert_eq!(out.exit_code, 0, "rebuild #{i} failed: {}", out.describe());
+        }
+        done.store(true, Ordering::SeqCst);
+    });
+
+    let violations = violations.into_inner().unwrap();
+    assert!(
+        violations.is_empty(),
+        "{} reads observed a partial or mixed cache:\n{}",
+        violations.len(),
+        violations.join("\n")
+    );
+
+    // REBUILDS is even, so the last rebuild wrote sources A.
+    let summary = cache_check::check_integrity(&cache)
+        .unwrap_or_else(|e| panic!("cache failed integrity check after rebuilds: {e}"));
+    assert_eq!(summary, summary_a, "final cache is not the last build");
+    assert_ne!(summary_a, summary_b);
+}
+
+/// Two `build --force` runs race into the same cache directory. The survivor
+/// must be one complete build, and neither run may crash.
+#[test]
+fn racing_force_builds() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let sources_a = fixture::documents_path("minimal");
+    let sources_b = fixture::documents_path("realistic");
+    let (seen_a, summary_a) = reference(&runner, &sources_a, &dir.path().join("ref-a"));
+    let (seen_b, summary_b) = reference(&runner, &sources_b, &dir.path().join("ref-b"));
+
+    for round in 0..RACE_ROUNDS {
+        let cache = dir.path().join(format!("race-{round}"));
+        let out = runner.build(&sources_a, &cache, false).unwrap();
+        assert_eq!(out.exit_code, 0, "initial build failed: {}", out.describe());
+
+        let barrier = Barrier::new(2);
+        let (out_a, out_b) = std::thread::scope(|s| {
+            let a = s.spawn(|| {
+                barrier.wait();
+                runner.build(&sources_a, &cache, true).unwrap()
+            });
+            let b = s.spawn(|| {
+                barrier.wait();
+                runner.build(&sources_b, &cache, true).unwrap()
+            });
+            (a.join().unwrap(), b.join().unwrap())
+        });
+
+        for (name, out) in [("A", &out_a), ("B", &out_b)] {
+            assert!(
+                // Internal errors are never acceptable under contention.
+                out.exit().is_some_and(|c| c != ExitCode::Internal),
+                "round {round}: racing build {name}: {}",
+                out.describe()
+            );
+        }
+        assert!(
+            out_a.exit_code == 0 || out_b.exit_code == 0,
+            "round {round}: both racing builds failed:\nA: {}\nB: {}",
+            out_a.describe(),
+            out_b.describe()
+        );
+
+        let summary = cache_check::check_integrity(&cache)
+            .unwrap_or_else(|e| panic!("round {round}: cache failed integrity check: {e}"));
+        let observed = observe(&runner, &cache);
+        if summary == summary_a {
+            assert_eq!(observed, seen_a, "round {round}: cache A serves different output");
+        } else if summary == summary_b {
+            assert_eq!(observed, seen_b, "round {round}: cache B serves different output");
+        } else {
+            panic!("round {round}: final cache is neither build: {summary:?}");
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Crash-atomicity tests: `context build --force` killed at arbitrary points.
+//!
+//! After a SIGKILL mid-rebuild the cache may hold the previous valid build, the
+//! new valid build, or be reported as `cache_invalid` / `cache_missing`.
+//! Silently wrong results are never acceptable.
+//!
+//! The kill schedule is seeded. Set `CONTEXT_CRASH_SEED` to replay a failure.
+
+use context_compat::cli_runner::{CliRunner, KillOutcome};
+use context_compat::exit_code::ExitCode;
+use context_compat::fixture;
+use context_compat::rng::Rng;
+use context_compat::source_tree::{SourceTree, WriteOptions};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+fn cli() -> Option<CliRunner> {
+    CliRunner::from_env()
+}
+
+const ITERATIONS: usize = 60;
+const DEFAULT_SEED: u64 = 0x00c0_ffee;
+
+const QUERY: &str = "deployment security";
+const BUDGET: usize = 4000;
+
+fn seed() -> u64 {
+    std::env::var("CONTEXT_CRASH_SEED")
+        .ok()
+        .map(|s| s.parse().expect("CONTEXT_CRASH_SEED must be a u64"))
+        .unwrap_or(DEFAULT_SEED)
+}
+
+/// A source tree large enough that kill points land inside the write phase.
+fn large_sources(dest: &Path) {
+    let mut tree = SourceTree::read(&fixture::documents_path("realistic")).unwrap();
+    let words = ["deployment", "security", "guide", "rollback", "audit", "cache", "token"];
+    let mut rng = Rng::new(7);
+    for n in 0..400 {
+        let len = 20 + rng.below(200) as usize;
+        let body: Vec<&str> = (0..len)
+            .map(|_| words[rng.below(words.len() as u64) as usize])
+            .collect();
+        tree.insert(format!("generated/doc{n:04}.md"), body.join(" "));
+    }
+    tree.write(dest, &WriteOptions::default()).unwrap();
+}
+
+/// What `inspect` and `resolve` print for one complete build.
+#[derive(Debug, PartialEq)]
+struct State {
+    inspect: String,
+    resolve: String,
+}
+
+fn state(runner: &CliRunner, cache: &Path) -> State {
+    let i = runner.inspect(cache).unwrap();
+    let r = runner.resolve(cache, QUERY, BUDGET).unwrap();
+    assert_eq!(i.exit(), Some(ExitCode::Success), "inspect failed: {}", i.describe());
+    assert_eq!(r.exit(), Some(ExitCode::Success), "resolve failed: {}", r.describe());
+    State {
+        inspect: i.stdout,
+        resolve: r.stdout,
+    }
+}
+
+/// SIGKILL `build --force` at randomized points and check that `inspect` and
+/// `resolve` on the leftovers only ever see an allowed state.
+#[test]
+fn killed_force_rebuild_leaves_allowed_state() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let sources_old = fixture::documents_path("minimal");
+    let sources_new = dir.path().join("sources-new");
+    large_sources(&sources_new);
+
+    let ref_old = dir.path().join("ref-old");
+    let ref_new = dir.path().join("ref-new");
+    assert_eq!(runner.build(&sources_old, &ref_old, false).unwrap().exit_code, 0);
+    let started = Instant::now();
+    let b = runner.build(&sources_new, &ref_new, false).unwrap();
+    let build_time = started.elapsed();
+    assert_eq!(b.exit_code, 0, "reference build failed: {}", b.describe());
+
+    let old = state(&runner, &ref_old);
+    let new = state(&runner, &ref_new);
+
+    // Kill anywhere from spawn to somewhat past a typical full build.
+    let window_us = (build_time.as_micros() as u64 * 3 / 2).max(1_000);
+    let seed = seed();
+    let mut rng = Rng::new(seed);
+    let mut killed = 0;
+
+    for i in 0..ITERATIONS {
+        let cache = dir.path().join(format!("cache-{i}"));
+        let out = runner.build(&sources_old, &cache, false).unwrap();
+        assert_eq!(out.exit_code, 0, "iteration {i}: initial build failed: {}", out.describe());
+
+        let after = Duration::from_micros(rng.below(window_us));
+        match runner.build_and_kill(&sources_new, &cache, true, after).unwrap() {
+            KillOutcome::Killed => killed += 1,
+            KillOutcome::Finished(out) => assert_eq!(
+                out.exit_code, 0,
+                "iteration {i}: unkilled rebuild failed: {}",
+                out.describe()
+            ),
+        }
+
+        let ctx = format!("iteration {i} (seed {seed}, killed after {after:?})");
+        let inspect = runner.inspect(&cache).unwrap();
+        let resolve = runner.resolve(&cache, QUERY, BUDGET).unwrap();
+
+        for (cmd, out) in [("inspect", &inspect), ("resolve", &resolve)] {
+            assert!(
+                matches!(
+                    out.exit(),
+                    Some(ExitCode::Success | ExitCode::CacheMissing | ExitCode::CacheInvalid)
+                ),
+                "{ctx}: {cmd} on leftovers: {}",
+                out.describe()
+            );
+            if out.exit() != Some(ExitCode::Success) {
+                assert!(out.stdout.is_empty(), "{ctx}: failing {cmd} wrote stdout: {}", out.stdout);
+            }
+        }
+
+        // inspect may report a broken cache as `valid: false` instead of failing.
+        let inspect_rejects = inspect.exit() != Some(ExitCode::Success)
+            || serde_json::from_str::<serde_json::Value>(inspect.stdout.trim())
+                .map(|v| v["valid"] == false)
+                .unwrap_or(false);
+
+        match (inspect_rejects, resolve.exit() == Some(ExitCode::Success)) {
+            (false, true) => {
+                let seen = State {
+                    inspect: inspect.stdout,
+                    resolve: resolve.stdout,
+                };
+                assert!(
+                    seen == old || seen == new,
+                    "{ctx}: leftovers serve neither the old nor the new cache:\n{seen:?}"
+                );
+            }
+            (true, false) => {}
+            (false, false) => panic!(
+                "{ctx}: inspect accepts the leftovers but resolve fails: {}",
+                resolve.describe()
+            ),
+            (true, true) => panic!(
+                "{ctx}: resolve serves a cache that inspect rejects: {}",
+                resolve.stdout
+            ),
+        }
+    }
+
+    eprintln!("crash atomicity: {killed}/{ITERATIONS} rebuilds killed (seed {seed})");
+}
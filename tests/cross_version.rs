@@ -15,7 +15,7 @@ fn current() -> Option<CliRunner> {
 fn previous() -> Option<CliRunner> {
     std::env::var("CONTEXT_PREV_BIN")
         .ok()
-        .map(|p| CliRunner::new(p))
+        .map(CliRunner::new)
 }
 
 /// Resolve output from current and previous binaries must be byte-identical.
@@ -55,12 +55,12 @@ fn resolve_matches_previous_binary() {
         assert_eq!(
             curr_out.exit_code, 0,
             "current binary failed on {cache_name}/{query_name}: {}",
-            curr_out.stderr
+            curr_out.describe()
         );
         assert_eq!(
             prev_out.exit_code, 0,
             "previous binary failed on {cache_name}/{query_name}: {}",
-            prev_out.stderr
+            prev_out.describe()
         );
 
         let curr_canon = fixture::canonicalize(&curr_out.stdout);
@@ -146,8 +146,8 @@ fn build_version_matches_previous_binary() {
     let bc = curr.build(&sources, &cache_curr, false).unwrap();
     let bp = prev.build(&sources, &cache_prev, false).unwrap();
 
-    assert_eq!(bc.exit_code, 0, "current build failed: {}", bc.stderr);
-    assert_eq!(bp.exit_code, 0, "previous build failed: {}", bp.stderr);
+    assert_eq!(bc.exit_code, 0, "current build failed: {}", bc.describe());
+    assert_eq!(bp.exit_code, 0, "previous build failed: {}", bp.describe());
 
     let ic = curr.inspect(&cache_curr).unwrap();
     let ip = prev.inspect(&cache_prev).unwrap();
@@ -1,7 +1,10 @@
 //! Determinism tests: same input twice produces byte-identical output.
 
-use context_compat::cli_runner::CliRunner;
+use context_compat::cli_runner::{CliEnv, CliRunner};
 use context_compat::fixture;
+use context_compat::source_tree::{FileOrder, Linking, Mtimes, SourceTree, WriteOptions};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 fn cli() -> Option<CliRunner> {
     CliRunner::from_env()
@@ -24,8 +27,8 @@ fn resolve_deterministic_minimal_basic() {
     let out1 = runner.resolve(&cache, &q.query, q.budget).unwrap();
     let out2 = runner.resolve(&cache, &q.query, q.budget).unwrap();
 
-    assert_eq!(out1.exit_code, 0, "first run failed: {}", out1.stderr);
-    assert_eq!(out2.exit_code, 0, "second run failed: {}", out2.stderr);
+    assert_eq!(out1.exit_code, 0, "first run failed: {}", out1.describe());
+    assert_eq!(out2.exit_code, 0, "second run failed: {}", out2.describe());
     assert_eq!(
         out1.stdout, out2.stdout,
         "resolve output is not deterministic"
@@ -104,6 +107,141 @@ fn resolve_deterministic_zero_budget() {
     );
 }
 
+// --- Environment perturbation ---
+
+/// Resolve scenarios rerun under every perturbation: (cache, query fixture).
+const PERTURBED_SCENARIOS: &[(&str, &str)] = &[
+    ("minimal", "basic"),
+    ("realistic", "basic"),
+    ("realistic", "multi_term"),
+    ("minimal", "zero_budget"),
+];
+
+/// A named process environment plus the `--cache` argument to pass under it.
+struct Perturbation {
+    name: String,
+    env: CliEnv,
+    cache: PathBuf,
+}
+
+fn with_vars(vars: &[(&str, &str)]) -> CliEnv {
+    CliEnv {
+        vars: vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        ..CliEnv::default()
+    }
+}
+
+/// Every process environment output must be independent of, by name.
+fn environments(scratch: &Path) -> Vec<(String, CliEnv)> {
+    let mut out = Vec::new();
+
+    for locale in ["C", "tr_TR.UTF-8", "ja_JP.UTF-8"] {
+        out.push((
+            format!("LANG/LC_ALL={locale}"),
+            with_vars(&[("LANG", locale), ("LC_ALL", locale)]),
+        ));
+    }
+    for tz in ["UTC", "Asia/Kolkata", "America/St_Johns", "Pacific/Kiritimati"] {
+        out.push((format!("TZ={tz}"), with_vars(&[("TZ", tz)])));
+    }
+    for mask in [0o000, 0o077] {
+        out.push((
+            format!("umask={mask:03o}"),
+            CliEnv {
+                umask: Some(mask),
+                ..CliEnv::default()
+            },
+        ));
+    }
+    for dir in [std::env::temp_dir(), scratch.to_path_buf()] {
+        out.push((
+            format!("working directory={}", dir.display()),
+            CliEnv {
+                current_dir: Some(dir),
+                ..CliEnv::default()
+            },
+        ));
+    }
+    let home = scratch.display().to_string();
+    out.push((format!("HOME={home}"), with_vars(&[("HOME", &home)])));
+    out.push((
+        "env_clear() environment".to_string(),
+        CliEnv {
+            clear: true,
+            ..CliEnv::default()
+        },
+    ));
+    out
+}
+
+/// Every environment the resolve output must be independent of, plus a
+/// relative `--cache` path.
+fn perturbations(cache_name: &str, scratch: &Path) -> Vec<Perturbation> {
+    let cache = fixture::cache_path(cache_name);
+    let mut out: Vec<Perturbation> = environments(scratch)
+        .into_iter()
+        .map(|(name, env)| Perturbation {
+            name,
+            env,
+            cache: cache.clone(),
+        })
+        .collect();
+    out.push(Perturbation {
+        name: "relative --cache path".to_string(),
+        env: CliEnv {
+            current_dir: Some(cache.parent().unwrap().to_path_buf()),
+            ..CliEnv::default()
+        },
+        cache: PathBuf::from(cache_name),
+    });
+    out
+}
+
+/// Every determinism scenario produces byte-identical stdout under varied
+/// locale, timezone, umask, working directory, HOME, a cleared environment,
+/// and relative versus absolute `--cache` paths.
+#[test]
+fn resolve_deterministic_under_env_perturbation() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let scratch = tempfile::tempdir().unwrap();
+
+    for (cache_name, query_name) in PERTURBED_SCENARIOS {
+        let cache = fixture::cache_path(cache_name);
+        let q = fixture::query(query_name);
+
+        let baseline = runner.resolve(&cache, &q.query, q.budget).unwrap();
+        assert_eq!(
+            baseline.exit_code, 0,
+            "baseline run failed for {cache_name}/{query_name}: {}",
+            baseline.describe()
+        );
+
+        for p in perturbations(cache_name, scratch.path()) {
+            let out = runner.resolve_in(&p.env, &p.cache, &q.query, q.budget).unwrap();
+            assert_eq!(
+                out.exit_code, 0,
+                "resolve {cache_name}/{query_name} failed under {}: {}",
+                p.name, out.describe()
+            );
+            assert_eq!(
+                out.stdout, baseline.stdout,
+                "resolve {cache_name}/{query_name} output changed under {}",
+                p.name
+            );
+        }
+    }
+}
+
 /// Build a cache twice from the same sources in different directories.
 /// Verifies path independence: manifest hashes, document file hashes, and
 /// resolve output must all be identical between the two builds.
@@ -125,8 +263,8 @@ fn build_deterministic() {
     let b1 = runner.build(&sources, &cache1, false).unwrap();
     let b2 = runner.build(&sources, &cache2, false).unwrap();
 
-    assert_eq!(b1.exit_code, 0, "first build failed: {}", b1.stderr);
-    assert_eq!(b2.exit_code, 0, "second build failed: {}", b2.stderr);
+    assert_eq!(b1.exit_code, 0, "first build failed: {}", b1.describe());
+    assert_eq!(b2.exit_code, 0, "second build failed: {}", b2.describe());
 
     // 1. Inspect output: cache_version, document_count, valid must match
     let i1 = runner.inspect(&cache1).unwrap();
@@ -202,8 +340,8 @@ fn build_deterministic_realistic() {
     let b1 = runner.build(&sources, &cache1, false).unwrap();
     let b2 = runner.build(&sources, &cache2, false).unwrap();
 
-    assert_eq!(b1.exit_code, 0, "first build failed: {}", b1.stderr);
-    assert_eq!(b2.exit_code, 0, "second build failed: {}", b2.stderr);
+    assert_eq!(b1.exit_code, 0, "first build failed: {}", b1.describe());
+    assert_eq!(b2.exit_code, 0, "second build failed: {}", b2.describe());
 
     // Manifest documents array must be identical (verifies deterministic sort order)
     let m1: serde_json::Value = serde_json::from_str(
@@ -218,3 +356,201 @@ fn build_deterministic_realistic() {
     assert_eq!(m1["cache_version"], m2["cache_version"]);
     assert_eq!(m1["documents"], m2["documents"]);
 }
+
+/// Sources of the build scenarios rerun under every perturbation.
+const PERTURBED_BUILDS: &[&str] = &["minimal", "realistic"];
+
+/// Every build scenario produces the same cache under the environments of
+/// [`resolve_deterministic_under_env_perturbation`], and with relative
+/// `--sources` and `--cache` paths.
+#[test]
+fn build_deterministic_under_env_perturbation() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let scratch = tempfile::tempdir().unwrap();
+    let work = tempfile::tempdir().unwrap();
+
+    for set in PERTURBED_BUILDS {
+        let sources = fixture::documents_path(set);
+        let baseline_cache = work.path().join(format!("{set}-baseline"));
+        let out = runner.build(&sources, &baseline_cache, false).unwrap();
+        assert_eq!(out.exit_code, 0, "baseline build of {set} failed: {}", out.describe());
+        let baseline = snapshot(&baseline_cache);
+
+        // (name, environment, --sources, --cache)
+        let mut runs: Vec<(String, CliEnv, PathBuf, PathBuf)> = environments(scratch.path())
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, env))| (name, env, sources.clone(), work.path().join(format!("{set}-{i}"))))
+            .collect();
+        runs.push((
+            "relative --sources path".to_string(),
+            CliEnv {
+                current_dir: Some(sources.parent().unwrap().to_path_buf()),
+                ..CliEnv::default()
+            },
+            PathBuf::from(set),
+            work.path().join(format!("{set}-relative-sources")),
+        ));
+        runs.push((
+            "relative --cache path".to_string(),
+            CliEnv {
+                current_dir: Some(work.path().to_path_buf()),
+                ..CliEnv::default()
+            },
+            sources.clone(),
+            PathBuf::from(format!("{set}-relative-cache")),
+        ));
+
+        for (name, env, sources, cache) in runs {
+            let out = runner.build_in(&env, &sources, &cache, false).unwrap();
+            assert_eq!(out.exit_code, 0, "build {set} failed under {name}: {}", out.describe());
+            assert!(
+                snapshot(&work.path().join(&cache)) == baseline,
+                "build {set} cache differs under {name}"
+            );
+        }
+    }
+}
+
+// --- Filesystem order and metadata independence ---
+
+/// Cache contents that must not depend on how the sources were laid down:
+/// manifest without `created_at`, raw `index.json`, and every document file.
+#[derive(Debug, PartialEq)]
+struct CacheSnapshot {
+    manifest: serde_json::Value,
+    index: Vec<u8>,
+    documents: BTreeMap<String, Vec<u8>>,
+}
+
+fn snapshot(cache: &Path) -> CacheSnapshot {
+    let mut manifest: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(cache.join("manifest.json")).unwrap(),
+    )
+    .unwrap();
+    manifest.as_object_mut().unwrap().remove("created_at");
+
+    let mut documents = BTreeMap::new();
+    for entry in std::fs::read_dir(cache.join("documents")).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        documents.insert(name, std::fs::read(&path).unwrap());
+    }
+
+    CacheSnapshot {
+        manifest,
+        index: std::fs::read(cache.join("index.json")).unwrap(),
+        documents,
+    }
+}
+
+/// Build from the same logical source tree materialized with files created in
+/// sorted, reversed and shuffled order, with varying mtimes, as hardlinks, and
+/// inside nested subdirectories. Every resulting cache must be byte-identical
+/// apart from the manifest's `created_at`.
+#[test]
+fn build_independent_of_filesystem_order() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let mut tree = SourceTree::read(&fixture::documents_path("realistic")).unwrap();
+    tree.add_dir(&fixture::documents_path("minimal"), Path::new("guides/nested"))
+        .unwrap();
+    tree.add_dir(&fixture::documents_path("tie_break"), Path::new("a/b/c"))
+        .unwrap();
+    tree.add_dir(&fixture::documents_path("tie_break"), Path::new("z"))
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let origin = dir.path().join("sources-sorted");
+
+    let variants: Vec<(&str, WriteOptions)> = vec![
+        ("sorted", WriteOptions::default()),
+        (
+            "reversed",
+            WriteOptions {
+                order: FileOrder::Reversed,
+                ..WriteOptions::default()
+            },
+        ),
+        (
+            "shuffled-1",
+            WriteOptions {
+                order: FileOrder::Shuffled(1),
+                ..WriteOptions::default()
+            },
+        ),
+        (
+            "shuffled-2-old-mtimes",
+            WriteOptions {
+                order: FileOrder::Shuffled(2),
+                mtimes: Mtimes::Sequential { start: 86_400, step: 3_600 },
+                ..WriteOptions::default()
+            },
+        ),
+        (
+            "reversed-descending-mtimes",
+            WriteOptions {
+                order: FileOrder::Reversed,
+                mtimes: Mtimes::Sequential { start: 4_000_000_000, step: -7 },
+                ..WriteOptions::default()
+            },
+        ),
+        (
+            "hardlinked-shuffled",
+            WriteOptions {
+                order: FileOrder::Shuffled(3),
+                linking: Linking::Hardlinked(origin.clone()),
+                ..WriteOptions::default()
+            },
+        ),
+    ];
+
+    let mut baseline: Option<CacheSnapshot> = None;
+    for (name, opts) in &variants {
+        let sources = if *name == "sorted" {
+            origin.clone()
+        } else {
+            dir.path().join(format!("sources-{name}"))
+        };
+        tree.write(&sources, opts).unwrap();
+
+        let cache = dir.path().join(format!("cache-{name}"));
+        let out = runner.build(&sources, &cache, false).unwrap();
+        assert_eq!(out.exit_code, 0, "build from {name} sources failed: {}", out.describe());
+
+        let snap = snapshot(&cache);
+        assert_eq!(
+            snap.manifest["document_count"],
+            tree.files().len(),
+            "build from {name} sources did not pick up every nested file"
+        );
+
+        match &baseline {
+            None => baseline = Some(snap),
+            Some(base) => {
+                assert_eq!(
+                    snap.manifest, base.manifest,
+                    "manifest (minus created_at) differs for {name} sources"
+                );
+                assert_eq!(snap.index, base.index, "index.json differs for {name} sources");
+                assert_eq!(
+                    snap.documents, base.documents,
+                    "document files differ for {name} sources"
+                );
+            }
+        }
+    }
+}
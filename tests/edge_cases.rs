@@ -0,0 +1,196 @@
+//! Edge-case document contracts over `fixtures/v0/documents/edge_cases`:
+//! the empty document, a term repeated hundreds of times, `large.md` under
+//! tight budgets, and non-ASCII content round-tripping.
+
+use context_compat::cli_runner::CliRunner;
+use context_compat::fixture;
+use context_compat::invariants;
+use context_compat::source_tree::{SourceTree, WriteOptions};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+fn cli() -> Option<CliRunner> {
+    CliRunner::from_env()
+}
+
+fn resolve(runner: &CliRunner, cache: &Path, query: &str, budget: usize) -> Value {
+    let out = runner.resolve(cache, query, budget).unwrap();
+    assert_eq!(out.exit_code, 0, "resolve {query:?}/{budget} failed: {}", out.describe());
+    let v: Value = serde_json::from_str(out.stdout.trim()).unwrap();
+    invariants::check_selection(&v)
+        .unwrap_or_else(|e| panic!("resolve {query:?}/{budget} violates invariants: {e}"));
+    v
+}
+
+/// Build `fixtures/v0/documents/edge_cases` into a fresh cache, so checks
+/// that need no golden do not depend on the committed one being recorded.
+fn build_edge_cases(runner: &CliRunner, dir: &Path) -> PathBuf {
+    let cache = dir.join("edge_cases");
+    let out = runner.build(&fixture::documents_path("edge_cases"), &cache, false).unwrap();
+    assert_eq!(out.exit_code, 0, "build failed: {}", out.describe());
+    cache
+}
+
+fn doc<'a>(v: &'a Value, id: &str) -> Option<&'a Value> {
+    v["documents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["id"] == id)
+}
+
+/// Resolve and inspect output over the edge-case cache match the goldens.
+#[test]
+fn golden_edge_cases() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let cache = fixture::require_cache("edge_cases");
+    let cases = [
+        ("basic", "edge_cases_basic"),
+        ("edge_tight_budget", "edge_cases_tight_budget"),
+        ("no_match", "edge_cases_no_match"),
+    ];
+    for (query_name, expected_name) in cases {
+        let q = fixture::query(query_name);
+        let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
+        assert_eq!(out.exit_code, 0, "resolve {query_name} failed: {}", out.describe());
+        assert_eq!(
+            fixture::canonicalize(&out.stdout),
+            fixture::require_expected(expected_name),
+            "golden output mismatch for '{expected_name}'"
+        );
+    }
+
+    let out = runner.inspect(&cache).unwrap();
+    assert_eq!(out.exit_code, 0, "inspect failed: {}", out.describe());
+    assert_eq!(
+        fixture::canonicalize(&out.stdout),
+        fixture::require_expected("inspect_edge_cases"),
+        "golden output mismatch for 'inspect_edge_cases'"
+    );
+}
+
+/// The empty document costs zero tokens, has no words and scores exactly 0.0,
+/// whether or not the query matches anything else.
+#[test]
+fn empty_document_tokens_and_score() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache = build_edge_cases(&runner, dir.path());
+    for query in ["deployment", "xyznotfound", ""] {
+        let v = resolve(&runner, &cache, query, 4000);
+        let empty = doc(&v, "empty.md")
+            .unwrap_or_else(|| panic!("{query:?}: empty.md should be selected"));
+        assert_eq!(empty["content"], "", "{query:?}: empty.md content");
+        assert_eq!(empty["tokens"], 0, "{query:?}: empty.md tokens");
+        assert_eq!(empty["score"].as_f64(), Some(0.0), "{query:?}: empty.md score");
+        assert_eq!(empty["why"]["total_words"], 0, "{query:?}: empty.md total_words");
+        assert_eq!(empty["why"]["term_matches"], 0, "{query:?}: empty.md term_matches");
+    }
+}
+
+/// A term repeated hundreds of times scores at most 1.0, with matches never
+/// exceeding the word count.
+#[test]
+fn repeated_term_score_bounded() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut tree = SourceTree::read(&fixture::documents_path("edge_cases")).unwrap();
+    tree.insert("repeated_500.md", vec!["deployment"; 500].join(" "));
+    tree.insert("repeated_mixed_case.md", vec!["Deployment DEPLOYMENT deployment"; 200].join(" "));
+    let sources = dir.path().join("sources");
+    let cache = dir.path().join("cache");
+    tree.write(&sources, &WriteOptions::default()).unwrap();
+    let out = runner.build(&sources, &cache, false).unwrap();
+    assert_eq!(out.exit_code, 0, "build failed: {}", out.describe());
+
+    for cache in [build_edge_cases(&runner, dir.path()), cache] {
+        let v = resolve(&runner, &cache, "deployment deployment", 1_000_000);
+        for d in v["documents"].as_array().unwrap() {
+            let score = d["score"].as_f64().unwrap();
+            let matches = d["why"]["term_matches"].as_u64().unwrap();
+            let words = d["why"]["total_words"].as_u64().unwrap();
+            assert!(score <= 1.0, "{} scored {score} > 1.0", d["id"]);
+            assert!(matches <= words, "{} has {matches} matches for {words} words", d["id"]);
+        }
+        let repeated = doc(&v, "repeated_terms.md").expect("repeated_terms.md should be selected");
+        assert_eq!(repeated["score"].as_f64(), Some(1.0));
+        assert_eq!(repeated["why"]["term_matches"], repeated["why"]["total_words"]);
+    }
+}
+
+/// `large.md` is selected only when the budget covers it: never when its token
+/// count does not fit, always when the budget covers every document.
+#[test]
+fn large_document_under_tight_budgets() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache = build_edge_cases(&runner, dir.path());
+    let full = resolve(&runner, &cache, "deployment", 1_000_000);
+    let large_tokens = doc(&full, "large.md").expect("large.md should be selected")["tokens"]
+        .as_u64()
+        .unwrap() as usize;
+    let total = full["selection"]["tokens_used"].as_u64().unwrap() as usize;
+    assert!(large_tokens > 1000, "large.md should be large, got {large_tokens} tokens");
+
+    for budget in [0, 1, 10, 300, large_tokens - 1] {
+        let v = resolve(&runner, &cache, "deployment", budget);
+        assert!(
+            doc(&v, "large.md").is_none(),
+            "large.md ({large_tokens} tokens) selected under budget {budget}"
+        );
+        assert!(v["selection"]["documents_excluded_by_budget"].as_u64().unwrap() >= 1);
+    }
+
+    let v = resolve(&runner, &cache, "deployment", total);
+    assert!(doc(&v, "large.md").is_some(), "large.md not selected under budget {total}");
+    assert_eq!(v["selection"]["tokens_used"], total);
+    assert_eq!(v["selection"]["documents_excluded_by_budget"], 0);
+}
+
+/// Non-ASCII content in `unicode.md` round-trips byte-for-byte.
+#[test]
+fn unicode_document_round_trips() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let source =
+        std::fs::read_to_string(fixture::documents_path("edge_cases").join("unicode.md")).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let v = resolve(&runner, &build_edge_cases(&runner, dir.path()), "café", 4000);
+    let unicode = doc(&v, "unicode.md").expect("unicode.md should be selected");
+    assert_eq!(unicode["content"].as_str().unwrap(), source);
+    assert_eq!(unicode["why"]["query_terms"], serde_json::json!(["café"]));
+}
@@ -31,7 +31,7 @@ fn golden_minimal_basic() {
     let q = fixture::query("basic");
     let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
 
-    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.stderr);
+    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.describe());
     assert_golden(&out.stdout, "minimal_basic");
 }
 
@@ -49,7 +49,7 @@ fn golden_minimal_zero_budget() {
     let q = fixture::query("zero_budget");
     let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
 
-    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.stderr);
+    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.describe());
     assert_golden(&out.stdout, "minimal_zero_budget");
 }
 
@@ -67,7 +67,7 @@ fn golden_realistic_basic() {
     let q = fixture::query("basic");
     let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
 
-    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.stderr);
+    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.describe());
     assert_golden(&out.stdout, "realistic_basic");
 }
 
@@ -85,7 +85,7 @@ fn golden_realistic_multi_term() {
     let q = fixture::query("multi_term");
     let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
 
-    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.stderr);
+    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.describe());
     assert_golden(&out.stdout, "realistic_multi_term");
 }
 
@@ -102,7 +102,7 @@ fn golden_inspect_minimal() {
     let cache = fixture::cache_path("minimal");
     let out = runner.inspect(&cache).unwrap();
 
-    assert_eq!(out.exit_code, 0, "inspect failed: {}", out.stderr);
+    assert_eq!(out.exit_code, 0, "inspect failed: {}", out.describe());
     assert_golden(&out.stdout, "inspect_minimal");
 }
 
@@ -119,6 +119,6 @@ fn golden_inspect_realistic() {
     let cache = fixture::cache_path("realistic");
     let out = runner.inspect(&cache).unwrap();
 
-    assert_eq!(out.exit_code, 0, "inspect failed: {}", out.stderr);
+    assert_eq!(out.exit_code, 0, "inspect failed: {}", out.describe());
     assert_golden(&out.stdout, "inspect_realistic");
 }
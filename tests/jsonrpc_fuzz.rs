@@ -0,0 +1,177 @@
+//! Grammar-based JSON-RPC fuzzing of the MCP server.
+//!
+//! `context_compat::jsonrpc_fuzz` generates hostile messages: deep nesting,
+//! multi-megabyte strings, invalid UTF-8, lone surrogates, odd ids, numbers
+//! beyond u64, duplicate keys, wrong types and byte-level mutations. After each
+//! message a `ping` is sent. The oracle: the server never exits, answers the
+//! ping with a result before the deadline, and every stdout line in between is
+//! JSON.
+//!
+//! The sequence is seeded (`CONTEXT_FUZZ_SEED`, `CONTEXT_FUZZ_CASES`). An input
+//! that breaks the oracle is saved under `fixtures/v0/regressions/jsonrpc/`
+//! and replayed on every run by `jsonrpc_regressions_replay`.
+
+use context_compat::fixture;
+use context_compat::jsonrpc_fuzz::{FuzzCase, JsonRpcFuzzer};
+use context_compat::mcp_runner::McpRunner;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const DEFAULT_SEED: u64 = 0x6a73_6f6e_7270_6321;
+const DEFAULT_CASES: usize = 200;
+const DEADLINE: Duration = Duration::from_secs(10);
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    match std::env::var(name) {
+        Ok(s) => s
+            .parse()
+            .unwrap_or_else(|_| panic!("{name} must be an integer, got {s:?}")),
+        Err(_) => default,
+    }
+}
+
+fn regressions_dir() -> PathBuf {
+    fixture::v0_root().join("regressions").join("jsonrpc")
+}
+
+fn spawn() -> Option<McpRunner> {
+    let root = fixture::v0_root().join("caches");
+    match McpRunner::from_env(&root) {
+        Some(Ok(mut runner)) => {
+            runner.initialize().unwrap();
+            Some(runner)
+        }
+        Some(Err(e)) => panic!("failed to spawn MCP server: {e}"),
+        None => {
+            eprintln!("MCP_SERVER_BIN not set, skipping");
+            None
+        }
+    }
+}
+
+/// Send one fuzz message, then a `ping` with id `fuzz-ping-{tag}`, and check
+/// the oracle. A string id no fuzz message uses keeps an error response to the
+/// message from passing for the ping's answer.
+fn check(runner: &mut McpRunner, bytes: &[u8], tag: &str) -> Result<(), String> {
+    let exited = |runner: &mut McpRunner| match runner.try_wait() {
+        Ok(Some(status)) => format!("server exited ({status})"),
+        _ => "server closed stdout".to_string(),
+    };
+
+    if let Err(e) = runner.write_bytes(bytes) {
+        return Err(format!("writing the message failed ({e}); {}", exited(runner)));
+    }
+    let id = format!("fuzz-ping-{tag}");
+    let ping = serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": "ping" });
+    if let Err(e) = runner.write(&ping.to_string()) {
+        return Err(format!("writing the ping failed ({e}); {}", exited(runner)));
+    }
+
+    let deadline = Instant::now() + DEADLINE;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let line = match runner.read_line_timeout(remaining) {
+            Ok(Some(line)) => line,
+            Ok(None) => return Err(format!("no ping response within {DEADLINE:?}")),
+            Err(e) => return Err(format!("stdout line is not UTF-8: {e}")),
+        };
+        if line.is_empty() {
+            return Err(exited(runner));
+        }
+        let v: serde_json::Value = serde_json::from_str(line.trim()).map_err(|e| {
+            let shown: String = line.chars().take(200).collect();
+            format!("stdout line is not JSON ({e}): {shown}")
+        })?;
+        if v["id"] == id.as_str() {
+            return match v.get("result") {
+                Some(_) => Ok(()),
+                None => Err(format!("ping answered without a result: {v}")),
+            };
+        }
+    }
+}
+
+/// Save a failing input so it is replayed on every future run.
+fn save_regression(case: &FuzzCase, seed: u64, index: usize) -> PathBuf {
+    let dir = regressions_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{}-{seed:016x}-{index}.bin", case.kind));
+    std::fs::write(&path, &case.bytes).unwrap();
+    path
+}
+
+fn describe(bytes: &[u8]) -> String {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(160)]).into_owned();
+    format!("{} bytes: {head}", bytes.len())
+}
+
+#[test]
+fn jsonrpc_fuzz() {
+    let mut runner = match spawn() {
+        Some(r) => r,
+        None => return,
+    };
+    let seed = env_or("CONTEXT_FUZZ_SEED", DEFAULT_SEED);
+    let cases = env_or("CONTEXT_FUZZ_CASES", DEFAULT_CASES);
+    eprintln!("jsonrpc fuzz: seed {seed:#x}, {cases} cases");
+
+    let mut fuzzer = JsonRpcFuzzer::new(seed);
+    for index in 0..cases {
+        let case = fuzzer.next_case();
+        if let Err(violation) = check(&mut runner, &case.bytes, &index.to_string()) {
+            let saved = save_regression(&case, seed, index);
+            panic!(
+                "case {index} ({}) with seed {seed:#x}: {violation}\n  input {}\n  saved to {}",
+                case.kind,
+                describe(&case.bytes),
+                saved.display()
+            );
+        }
+    }
+}
+
+/// Every saved crashing input passes the oracle on a fresh server.
+#[test]
+fn jsonrpc_regressions_replay() {
+    if std::env::var("MCP_SERVER_BIN").is_err() {
+        eprintln!("MCP_SERVER_BIN not set, skipping");
+        return;
+    }
+    let mut inputs: Vec<PathBuf> = match std::fs::read_dir(regressions_dir()) {
+        Ok(rd) => rd
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "bin"))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    inputs.sort();
+
+    for input in &inputs {
+        let bytes = std::fs::read(input).unwrap();
+        let mut runner = spawn().unwrap();
+        if let Err(violation) = check(&mut runner, &bytes, &name(input)) {
+            panic!("{}: {violation}\n  input {}", name(input), describe(&bytes));
+        }
+    }
+}
+
+fn name(path: &Path) -> String {
+    path.file_name().unwrap().to_string_lossy().into_owned()
+}
+
+/// The generator is deterministic and never emits a raw newline.
+#[test]
+fn fuzzer_is_deterministic() {
+    let mut a = JsonRpcFuzzer::new(7);
+    let mut b = JsonRpcFuzzer::new(7);
+    let mut kinds = std::collections::BTreeSet::new();
+    for _ in 0..300 {
+        let (x, y) = (a.next_case(), b.next_case());
+        assert_eq!(x.kind, y.kind);
+        assert_eq!(x.bytes, y.bytes);
+        assert!(!x.bytes.contains(&b'\n'), "{} case contains a newline", x.kind);
+        kinds.insert(x.kind);
+    }
+    assert_eq!(kinds.len(), JsonRpcFuzzer::kinds().len(), "not every kind generated");
+}
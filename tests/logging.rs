@@ -0,0 +1,187 @@
+//! `logging/setLevel` and the `notifications/message` contract.
+//!
+//! If `initialize` advertises `logging`, the level is set to each RFC 5424
+//! severity in turn and a mix of succeeding and failing requests is sent.
+//! Every `notifications/message` must be well-formed and at or above the set
+//! level. Every stdout line, log traffic included, must be a JSON-RPC 2.0
+//! message. Without the capability `logging/setLevel` is method-not-found.
+
+use context_compat::fixture;
+use context_compat::mcp_runner::McpRunner;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// RFC 5424 severities, least to most severe, as MCP spells them.
+const LEVELS: &[&str] = &[
+    "debug",
+    "info",
+    "notice",
+    "warning",
+    "error",
+    "critical",
+    "alert",
+    "emergency",
+];
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const TIMEOUT: Duration = Duration::from_secs(10);
+const QUIET: Duration = Duration::from_millis(300);
+
+fn mcp() -> Option<McpRunner> {
+    let cache_root = fixture::v0_root().join("caches");
+    match McpRunner::from_env(&cache_root) {
+        Some(Ok(runner)) => Some(runner),
+        Some(Err(e)) => panic!("failed to spawn MCP server: {e}"),
+        None => {
+            eprintln!("MCP_SERVER_BIN not set, skipping");
+            None
+        }
+    }
+}
+
+/// Spawn, initialize and report whether `logging` is advertised.
+fn initialized() -> Option<(McpRunner, bool)> {
+    let mut runner = mcp()?;
+    let response = runner.initialize().unwrap();
+    let v: Value = serde_json::from_str(response.trim()).unwrap();
+    let logging = v["result"]["capabilities"].get("logging").is_some();
+    Some((runner, logging))
+}
+
+/// Send a request and wait for its response; stdout must stay JSON throughout.
+fn call(runner: &mut McpRunner, method: &str, params: Value) -> Value {
+    let id = runner.request_async(method, params).unwrap();
+    let response = runner
+        .wait_for(id, TIMEOUT)
+        .unwrap_or_else(|e| panic!("{method}: {e}"))
+        .unwrap_or_else(|| panic!("{method}: no response within {TIMEOUT:?}"));
+    assert_eq!(response["jsonrpc"], "2.0", "{method}: {response}");
+    response
+}
+
+fn rank(level: &str) -> Option<usize> {
+    LEVELS.iter().position(|l| *l == level)
+}
+
+/// Requests that give a server something to log about, at several severities.
+fn traffic(runner: &mut McpRunner) {
+    call(runner, "ping", json!({}));
+    call(runner, "tools/list", json!({}));
+    let resolve = |cache: &str| {
+        json!({
+            "name": "context.resolve",
+            "arguments": { "cache": cache, "query": "deployment", "budget": 4000 }
+        })
+    };
+    call(runner, "tools/call", resolve("minimal"));
+    call(runner, "tools/call", resolve("missing"));
+    call(
+        runner,
+        "tools/call",
+        json!({ "name": "context.inspect_cache", "arguments": { "cache": "minimal" } }),
+    );
+    call(runner, "unknown/method", json!({}));
+}
+
+/// Check every leftover stdout message against `min`, returning how many
+/// log messages there were. Anything besides responses, already consumed,
+/// must be a JSON-RPC notification.
+fn check_messages(messages: &[Value], min: &str) -> usize {
+    let min_rank = rank(min).unwrap();
+    let mut count = 0;
+    for n in messages {
+        assert_eq!(n["jsonrpc"], "2.0", "level {min}: not JSON-RPC framed: {n}");
+        assert!(n["method"].is_string(), "level {min}: not a notification: {n}");
+        assert!(n.get("id").is_none(), "level {min}: notification with id: {n}");
+        if n["method"] != "notifications/message" {
+            continue;
+        }
+        count += 1;
+        let params = &n["params"];
+        let level = params["level"]
+            .as_str()
+            .unwrap_or_else(|| panic!("level {min}: message without string level: {n}"));
+        let level_rank =
+            rank(level).unwrap_or_else(|| panic!("level {min}: unknown level {level:?}: {n}"));
+        assert!(level_rank >= min_rank, "level {min}: {level} message emitted: {n}");
+        assert!(params.get("data").is_some(), "level {min}: message without data: {n}");
+        if let Some(logger) = params.get("logger") {
+            assert!(logger.is_string(), "level {min}: logger is not a string: {n}");
+        }
+    }
+    count
+}
+
+/// At every level, only messages at or above it arrive, all well-formed.
+#[test]
+fn set_level_filters_messages() {
+    let (mut runner, logging) = match initialized() {
+        Some(r) => r,
+        None => return,
+    };
+    if !logging {
+        eprintln!("logging capability not advertised, skipping");
+        return;
+    }
+
+    for level in LEVELS {
+        let response = call(&mut runner, "logging/setLevel", json!({ "level": level }));
+        assert!(response.get("error").is_none(), "setLevel {level} failed: {response}");
+        assert!(response["result"].is_object(), "setLevel {level}: {response}");
+        // Messages emitted before the response may predate the new level.
+        check_messages(&runner.take_all(), LEVELS[0]);
+
+        traffic(&mut runner);
+        runner.collect(QUIET).unwrap();
+        let count = check_messages(&runner.take_all(), level);
+        eprintln!("level {level}: {count} messages");
+    }
+}
+
+/// Unknown or missing levels are rejected as invalid params, not ignored.
+#[test]
+fn set_level_rejects_invalid_levels() {
+    let (mut runner, logging) = match initialized() {
+        Some(r) => r,
+        None => return,
+    };
+    if !logging {
+        eprintln!("logging capability not advertised, skipping");
+        return;
+    }
+
+    for params in [
+        json!({ "level": "verbose" }),
+        json!({ "level": "DEBUG" }),
+        json!({ "level": 7 }),
+        json!({}),
+    ] {
+        let response = call(&mut runner, "logging/setLevel", params.clone());
+        let code = response["error"]["code"]
+            .as_i64()
+            .unwrap_or_else(|| panic!("setLevel {params} accepted: {response}"));
+        assert_eq!(code, INVALID_PARAMS, "setLevel {params}: {response}");
+    }
+    call(&mut runner, "ping", json!({}));
+}
+
+/// Without the capability, `logging/setLevel` does not exist and nothing is logged.
+#[test]
+fn set_level_absent_without_capability() {
+    let (mut runner, logging) = match initialized() {
+        Some(r) => r,
+        None => return,
+    };
+    if logging {
+        return;
+    }
+
+    let response = call(&mut runner, "logging/setLevel", json!({ "level": "debug" }));
+    assert_eq!(response["error"]["code"], METHOD_NOT_FOUND, "{response}");
+
+    traffic(&mut runner);
+    runner.collect(QUIET).unwrap();
+    let count = check_messages(&runner.take_all(), LEVELS[0]);
+    assert_eq!(count, 0, "log messages without logging capability");
+}
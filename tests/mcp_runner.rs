@@ -0,0 +1,55 @@
+//! Harness self-tests for `McpRunner` stdout, stderr and shutdown handling.
+//!
+//! These run stub shell scripts in place of `mcp-context-server`, so they
+//! need no env vars and run everywhere `sh` exists (unix only).
+#![cfg(unix)]
+
+use context_compat::mcp_runner::McpRunner;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A runner for an executable script with the given body.
+fn stub(dir: &Path, body: &str) -> McpRunner {
+    let path = dir.join("mcp-context-server");
+    std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    McpRunner::new(path, dir).unwrap()
+}
+
+fn next(runner: &mut McpRunner) -> Result<String, std::io::Error> {
+    runner
+        .read_line_timeout(TIMEOUT)
+        .map(|line| line.expect("no line within timeout"))
+}
+
+#[test]
+fn invalid_utf8_line_loses_only_itself() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut runner = stub(dir.path(), r"printf 'ok1\n\377\376 bad\nnext\nlast\n'");
+
+    assert_eq!(next(&mut runner).unwrap(), "ok1\n");
+    assert!(next(&mut runner).is_err(), "invalid UTF-8 line not reported");
+    assert_eq!(next(&mut runner).unwrap(), "next\n");
+    assert_eq!(next(&mut runner).unwrap(), "last\n");
+}
+
+#[test]
+fn chatty_stderr_does_not_block() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut runner = stub(dir.path(), "head -c 1048576 /dev/zero >&2; echo done");
+
+    assert_eq!(next(&mut runner).unwrap(), "done\n");
+}
+
+#[test]
+fn wedged_server_is_killed_on_drop() {
+    let dir = tempfile::tempdir().unwrap();
+    let runner = stub(dir.path(), "exec sleep 60");
+
+    let started = Instant::now();
+    drop(runner);
+    assert!(started.elapsed() < TIMEOUT, "drop waited {:?}", started.elapsed());
+}
@@ -0,0 +1,330 @@
+//! Metamorphic tests: relations between `resolve` outputs on related inputs.
+//!
+//! Goldens only cover cases whose answers are already known. These relations
+//! hold for any corpus, so they run across every committed fixture cache and a
+//! handful of seeded generated corpora:
+//!
+//! - raising the budget never drops a previously selected document
+//! - adding a zero-match document never changes the relative order of the others
+//! - permuting query terms leaves scores unchanged
+//! - renaming a document only changes tie-break positions
+//! - duplicating the corpus doubles `documents_considered`
+
+use context_compat::cli_runner::CliRunner;
+use context_compat::fixture;
+use context_compat::rng::Rng;
+use context_compat::source_tree::{SourceTree, WriteOptions};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+fn cli() -> Option<CliRunner> {
+    CliRunner::from_env()
+}
+
+const FIXTURE_SETS: &[&str] = &["minimal", "realistic", "tie_break"];
+const GENERATED_SEEDS: &[u64] = &[1, 2, 3, 4];
+
+const QUERIES: &[&str] = &["deployment", "deployment security", "guide alpha deployment", "hello world", ""];
+const BUDGETS: &[usize] = &[0, 1, 3, 5, 8, 13, 21, 50, 100, 4000];
+const LARGE_BUDGET: usize = 1_000_000;
+
+const WORDS: &[&str] = &[
+    "deployment", "Deployment", "security", "guide", "guide.", "alpha", "bravo", "hello", "world",
+    "rollback",
+];
+
+struct Corpus {
+    name: String,
+    tree: SourceTree,
+    /// Committed cache built from `tree`, for fixture document sets.
+    prebuilt: Option<PathBuf>,
+}
+
+impl Corpus {
+    /// The committed cache if there is one, otherwise a fresh build under `dir`.
+    fn cache(&self, runner: &CliRunner, dir: &Path) -> PathBuf {
+        match &self.prebuilt {
+            Some(cache) => cache.clone(),
+            None => build(runner, &self.tree, dir, &self.name),
+        }
+    }
+}
+
+/// Every fixture document set (with its committed cache) plus seeded generated corpora.
+fn corpora() -> Vec<Corpus> {
+    let mut out: Vec<Corpus> = FIXTURE_SETS
+        .iter()
+        .map(|name| Corpus {
+            name: (*name).to_string(),
+            tree: SourceTree::read(&fixture::documents_path(name)).unwrap(),
+            prebuilt: Some(fixture::cache_path(name)),
+        })
+        .collect();
+
+    for &seed in GENERATED_SEEDS {
+        let mut rng = Rng::new(seed);
+        let mut tree = SourceTree::new();
+        for n in 0..(3 + rng.below(10)) {
+            let len = rng.below(30) as usize;
+            let words: Vec<&str> = (0..len)
+                .map(|_| WORDS[rng.below(WORDS.len() as u64) as usize])
+                .collect();
+            tree.insert(format!("gen{n:02}.md"), words.join(" "));
+        }
+        out.push(Corpus {
+            name: format!("generated-{seed}"),
+            tree,
+            prebuilt: None,
+        });
+    }
+    out
+}
+
+fn build(runner: &CliRunner, tree: &SourceTree, dir: &Path, name: &str) -> PathBuf {
+    let sources = dir.join(format!("{name}-sources"));
+    let cache = dir.join(format!("{name}-cache"));
+    tree.write(&sources, &WriteOptions::default()).unwrap();
+    let out = runner.build(&sources, &cache, false).unwrap();
+    assert_eq!(out.exit_code, 0, "build of {name} failed: {}", out.describe());
+    cache
+}
+
+fn resolve(runner: &CliRunner, cache: &Path, query: &str, budget: usize) -> Value {
+    let out = runner.resolve(cache, query, budget).unwrap();
+    assert_eq!(
+        out.exit_code, 0,
+        "resolve {query:?}/{budget} against {} failed: {}",
+        cache.display(),
+        out.describe()
+    );
+    serde_json::from_str(out.stdout.trim()).unwrap()
+}
+
+fn ids(v: &Value) -> Vec<String> {
+    v["documents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn scores(v: &Value) -> BTreeMap<String, Value> {
+    v["documents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| (d["id"].as_str().unwrap().to_string(), d["score"].clone()))
+        .collect()
+}
+
+/// Raising the budget never drops a previously selected document.
+#[test]
+fn raising_budget_keeps_selected_documents() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    for corpus in corpora() {
+        let cache = corpus.cache(&runner, dir.path());
+        for query in QUERIES {
+            let mut prev: Option<(usize, Vec<String>)> = None;
+            for &budget in BUDGETS {
+                let selected = ids(&resolve(&runner, &cache, query, budget));
+                if let Some((prev_budget, prev_selected)) = &prev {
+                    for id in prev_selected {
+                        assert!(
+                            selected.contains(id),
+                            "{}: {query:?} raising budget {prev_budget} -> {budget} dropped {id}",
+                            corpus.name
+                        );
+                    }
+                }
+                prev = Some((budget, selected));
+            }
+        }
+    }
+}
+
+/// Adding a document that matches no query term never changes the relative
+/// order of the other documents.
+#[test]
+fn zero_match_document_keeps_relative_order() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    for corpus in corpora() {
+        let cache = corpus.cache(&runner, dir.path());
+
+        for (k, filler_id) in ["0000-filler.md", "mmmm-filler.md", "zzzz-filler.md"]
+            .iter()
+            .enumerate()
+        {
+            let mut extended = corpus.tree.clone();
+            extended.insert(*filler_id, "xyznomatch qqqfiller unrelated words");
+            let name = format!("{}-filler{k}", corpus.name);
+            let extended_cache = build(&runner, &extended, dir.path(), &name);
+
+            for query in QUERIES {
+                let before = ids(&resolve(&runner, &cache, query, LARGE_BUDGET));
+                let after: Vec<String> = ids(&resolve(&runner, &extended_cache, query, LARGE_BUDGET))
+                    .into_iter()
+                    .filter(|id| id != filler_id)
+                    .collect();
+                assert_eq!(
+                    after, before,
+                    "{}: {query:?} adding {filler_id} reordered the other documents",
+                    corpus.name
+                );
+            }
+        }
+    }
+}
+
+/// Permuting the query terms leaves every document's score unchanged.
+#[test]
+fn permuting_query_terms_keeps_scores() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    for corpus in corpora() {
+        let cache = corpus.cache(&runner, dir.path());
+        for query in QUERIES {
+            let terms: Vec<&str> = query.split_whitespace().collect();
+            let baseline = resolve(&runner, &cache, query, LARGE_BUDGET);
+
+            let mut rng = Rng::new(terms.len() as u64);
+            for _ in 0..4 {
+                let mut permuted = terms.clone();
+                rng.shuffle(&mut permuted);
+                let permuted = permuted.join(" ");
+                let v = resolve(&runner, &cache, &permuted, LARGE_BUDGET);
+                assert_eq!(
+                    scores(&v),
+                    scores(&baseline),
+                    "{}: scores for {permuted:?} differ from {query:?}",
+                    corpus.name
+                );
+            }
+        }
+    }
+}
+
+/// Renaming a document keeps every score and the sequence of scores; only the
+/// positions of equal-score documents may move.
+#[test]
+fn renaming_document_only_moves_ties() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    for corpus in corpora() {
+        let cache = corpus.cache(&runner, dir.path());
+
+        for (k, (original, content)) in corpus.tree.files().iter().enumerate() {
+            let original = original.to_string_lossy().replace('\\', "/");
+            for (j, renamed) in ["0-first.md", "~last.md"].iter().enumerate() {
+                let mut tree = SourceTree::new();
+                for (path, c) in corpus.tree.files() {
+                    if path.to_string_lossy().replace('\\', "/") != original {
+                        tree.insert(path.clone(), c.clone());
+                    }
+                }
+                tree.insert(*renamed, content.clone());
+                let name = format!("{}-rename{k}-{j}", corpus.name);
+                let renamed_cache = build(&runner, &tree, dir.path(), &name);
+
+                for query in QUERIES {
+                    let before = resolve(&runner, &cache, query, LARGE_BUDGET);
+                    let after = resolve(&runner, &renamed_cache, query, LARGE_BUDGET);
+
+                    let mut expected = scores(&before);
+                    let score = expected.remove(&original).unwrap();
+                    expected.insert(renamed.to_string(), score);
+                    assert_eq!(
+                        scores(&after),
+                        expected,
+                        "{}: {query:?} renaming {original} -> {renamed} changed scores",
+                        corpus.name
+                    );
+
+                    let sequence = |v: &Value| -> Vec<Value> {
+                        v["documents"]
+                            .as_array()
+                            .unwrap()
+                            .iter()
+                            .map(|d| d["score"].clone())
+                            .collect()
+                    };
+                    assert_eq!(
+                        sequence(&after),
+                        sequence(&before),
+                        "{}: {query:?} renaming {original} -> {renamed} moved it across a score boundary",
+                        corpus.name
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Duplicating every document under a second directory doubles `documents_considered`.
+#[test]
+fn duplicating_corpus_doubles_considered() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    for corpus in corpora() {
+        let cache = corpus.cache(&runner, dir.path());
+
+        let mut doubled = SourceTree::new();
+        for (path, content) in corpus.tree.files() {
+            doubled.insert(Path::new("a").join(path), content.clone());
+            doubled.insert(Path::new("b").join(path), content.clone());
+        }
+        let doubled_cache = build(&runner, &doubled, dir.path(), &format!("{}-doubled", corpus.name));
+
+        for query in QUERIES {
+            for &budget in &[0, LARGE_BUDGET] {
+                let single = resolve(&runner, &cache, query, budget);
+                let double = resolve(&runner, &doubled_cache, query, budget);
+                let considered = single["selection"]["documents_considered"].as_u64().unwrap();
+                assert_eq!(
+                    double["selection"]["documents_considered"].as_u64().unwrap(),
+                    2 * considered,
+                    "{}: {query:?}/{budget} duplicating the corpus did not double documents_considered",
+                    corpus.name
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! CLI vs MCP parity: every scenario runs through both `context` and
+//! `mcp-context-server`, which must agree.
+//!
+//! - `resolve` stdout and the `context.resolve` tool text parse to identical JSON.
+//! - `inspect` stdout and the `context.inspect_cache` tool text parse to identical JSON.
+//! - A failing CLI exit code maps to the MCP `error.code` for the same input,
+//!   e.g. exit 4 with `cache_missing` (see `ExitCode::mcp_code`).
+//!
+//! Requires both `CONTEXT_CLI_BIN` and `MCP_SERVER_BIN`; skips otherwise.
+
+use context_compat::cli_runner::{CliOutput, CliRunner};
+use context_compat::exit_code::ExitCode;
+use context_compat::fixture;
+use context_compat::mcp_runner::McpRunner;
+use context_compat::source_tree::{SourceTree, WriteOptions};
+use serde_json::{json, Value};
+use std::path::Path;
+
+fn runners(cache_root: &Path) -> Option<(CliRunner, McpRunner)> {
+    let cli = match CliRunner::from_env() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return None;
+        }
+    };
+    let mcp = match McpRunner::from_env(cache_root) {
+        Some(Ok(r)) => r,
+        Some(Err(e)) => panic!("failed to spawn MCP server: {e}"),
+        None => {
+            eprintln!("MCP_SERVER_BIN not set, skipping");
+            return None;
+        }
+    };
+    Some((cli, mcp))
+}
+
+/// What one side produced for a scenario, normalized for comparison.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    /// Successful output, parsed.
+    Ok(Value),
+    /// A failure carrying a frozen MCP error code (CLI codes mapped through `ExitCode`).
+    Failed(&'static str),
+    /// CLI usage error, or an MCP JSON-RPC level error: rejected before
+    /// reaching the engine, so no error code to compare.
+    Rejected,
+}
+
+fn cli_outcome(out: &CliOutput, ctx: &str) -> Outcome {
+    match out.exit() {
+        Some(ExitCode::Success) => Outcome::Ok(
+            serde_json::from_str(out.stdout.trim())
+                .unwrap_or_else(|e| panic!("{ctx}: CLI stdout is not JSON: {e}")),
+        ),
+        Some(ExitCode::Usage) => Outcome::Rejected,
+        Some(code) => Outcome::Failed(code.mcp_code().unwrap()),
+        None => panic!("{ctx}: CLI exited outside the contract: {}", out.describe()),
+    }
+}
+
+fn mcp_outcome(mcp: &mut McpRunner, tool: &str, arguments: Value, ctx: &str) -> Outcome {
+    let response = mcp.call_tool(tool, arguments).unwrap();
+    let v: Value = serde_json::from_str(response.trim())
+        .unwrap_or_else(|e| panic!("{ctx}: MCP response is not JSON: {e}: {response}"));
+    if v.get("error").is_some() {
+        return Outcome::Rejected;
+    }
+    let result = &v["result"];
+    let text = result["content"][0]["text"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{ctx}: MCP result has no text content: {v}"));
+    let inner: Value = serde_json::from_str(text.trim())
+        .unwrap_or_else(|e| panic!("{ctx}: MCP tool text is not JSON: {e}: {text}"));
+    if result["isError"] == true {
+        let code = inner["error"]["code"].as_str().unwrap_or_default();
+        match ExitCode::from_mcp_code(code) {
+            Some(exit) => Outcome::Failed(exit.mcp_code().unwrap()),
+            None => panic!("{ctx}: MCP returned an unfrozen error code {code:?}"),
+        }
+    } else {
+        Outcome::Ok(inner)
+    }
+}
+
+/// Assert the two outcomes agree. A CLI usage error only requires the MCP
+/// side to fail in some way.
+fn assert_parity(cli: Outcome, mcp: Outcome, ctx: &str) {
+    match (&cli, &mcp) {
+        (Outcome::Rejected, Outcome::Rejected | Outcome::Failed(_)) => {}
+        _ => assert_eq!(cli, mcp, "{ctx}: CLI and MCP disagree"),
+    }
+}
+
+fn resolve_parity(cli: &CliRunner, mcp: &mut McpRunner, root: &Path, cache: &str, query: &str, budget: i64) {
+    let ctx = format!("resolve {cache:?} {query:?} {budget}");
+    let cache_path = root.join(cache);
+    let out = cli
+        .run([
+            "resolve".as_ref(),
+            "--cache".as_ref(),
+            cache_path.as_os_str(),
+            "--query".as_ref(),
+            query.as_ref(),
+            "--budget".as_ref(),
+            budget.to_string().as_ref(),
+        ])
+        .unwrap();
+    let args = json!({ "cache": cache, "query": query, "budget": budget });
+    assert_parity(
+        cli_outcome(&out, &ctx),
+        mcp_outcome(mcp, "context.resolve", args, &ctx),
+        &ctx,
+    );
+}
+
+fn inspect_parity(cli: &CliRunner, mcp: &mut McpRunner, root: &Path, cache: &str) {
+    let ctx = format!("inspect {cache:?}");
+    let out = cli.inspect(&root.join(cache)).unwrap();
+    let args = json!({ "cache": cache });
+    assert_parity(
+        cli_outcome(&out, &ctx),
+        mcp_outcome(mcp, "context.inspect_cache", args, &ctx),
+        &ctx,
+    );
+}
+
+/// Query fixtures resolved against each committed cache.
+const FIXTURE_SCENARIOS: &[(&str, &[&str])] = &[
+    ("minimal", &["basic", "zero_budget", "multi_term", "empty_query", "tight_budget", "no_match"]),
+    ("realistic", &["basic", "zero_budget", "multi_term", "empty_query", "tight_budget", "no_match"]),
+    ("tie_break", &["basic", "tie_break", "no_match"]),
+    ("edge_cases", &["basic", "edge_tight_budget", "no_match"]),
+    ("unicode", &["unicode_nfc", "unicode_nfd", "unicode_turkish_dotted", "unicode_cjk", "unicode_zwj"]),
+];
+
+/// Every fixture query gives the same selection JSON through both surfaces.
+#[test]
+fn resolve_parity_on_fixture_caches() {
+    let root = fixture::v0_root().join("caches");
+    let (cli, mut mcp) = match runners(&root) {
+        Some(r) => r,
+        None => return,
+    };
+    mcp.initialize().unwrap();
+
+    for (cache, queries) in FIXTURE_SCENARIOS {
+        fixture::require_cache(cache);
+        for name in *queries {
+            let q = fixture::query(name);
+            resolve_parity(&cli, &mut mcp, &root, cache, &q.query, q.budget as i64);
+        }
+    }
+}
+
+/// `inspect` matches `context.inspect_cache` on every committed cache,
+/// including the hand-crafted future version.
+#[test]
+fn inspect_parity_on_fixture_caches() {
+    let root = fixture::v0_root().join("caches");
+    let (cli, mut mcp) = match runners(&root) {
+        Some(r) => r,
+        None => return,
+    };
+    mcp.initialize().unwrap();
+
+    for cache in ["minimal", "realistic", "tie_break", "edge_cases", "unicode", "future_version"] {
+        fixture::require_cache(cache);
+        inspect_parity(&cli, &mut mcp, &root, cache);
+    }
+}
+
+/// Write a copy of a fixture cache under `root/name`, then let `damage` break it.
+fn broken_cache(root: &Path, name: &str, from: &str, damage: impl FnOnce(&Path)) {
+    let dest = root.join(name);
+    SourceTree::read(&fixture::cache_path(from))
+        .unwrap()
+        .write(&dest, &WriteOptions::default())
+        .unwrap();
+    damage(&dest);
+}
+
+/// Broken caches fail with corresponding exit and MCP error codes.
+#[test]
+fn failure_parity_on_broken_caches() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+
+    broken_cache(root, "intact", "minimal", |_| {});
+    std::fs::create_dir(root.join("no_manifest")).unwrap();
+    broken_cache(root, "corrupt_manifest", "minimal", |c| {
+        std::fs::write(c.join("manifest.json"), "not valid json").unwrap();
+    });
+    broken_cache(root, "empty_manifest", "minimal", |c| {
+        std::fs::write(c.join("manifest.json"), "").unwrap();
+    });
+    broken_cache(root, "truncated_index", "realistic", |c| {
+        let index = std::fs::read(c.join("index.json")).unwrap();
+        std::fs::write(c.join("index.json"), &index[..index.len() / 2]).unwrap();
+    });
+    broken_cache(root, "missing_documents", "realistic", |c| {
+        std::fs::remove_dir_all(c.join("documents")).unwrap();
+    });
+    broken_cache(root, "manifest_is_dir", "minimal", |c| {
+        std::fs::remove_file(c.join("manifest.json")).unwrap();
+        std::fs::create_dir(c.join("manifest.json")).unwrap();
+    });
+    broken_cache(root, "future_version", "future_version", |_| {});
+    std::fs::write(root.join("plain_file"), "not a cache").unwrap();
+
+    let (cli, mut mcp) = match runners(root) {
+        Some(r) => r,
+        None => return,
+    };
+    mcp.initialize().unwrap();
+
+    for cache in [
+        "intact",
+        "missing",
+        "no_manifest",
+        "corrupt_manifest",
+        "empty_manifest",
+        "truncated_index",
+        "missing_documents",
+        "manifest_is_dir",
+        "future_version",
+        "plain_file",
+    ] {
+        resolve_parity(&cli, &mut mcp, root, cache, "deployment", 4000);
+        inspect_parity(&cli, &mut mcp, root, cache);
+    }
+}
+
+/// Query and budget validation fails the same way on both surfaces.
+#[test]
+fn failure_parity_on_invalid_arguments() {
+    let root = fixture::v0_root().join("caches");
+    let (cli, mut mcp) = match runners(&root) {
+        Some(r) => r,
+        None => return,
+    };
+    mcp.initialize().unwrap();
+
+    let cases: &[(&str, i64)] = &[
+        ("", 4000),
+        ("   ", 4000),
+        ("\t\n", 4000),
+        ("deployment", 0),
+        ("deployment", -1),
+        ("deployment", i64::MAX),
+        ("deployment\u{7}security", 4000),
+    ];
+    for (query, budget) in cases {
+        resolve_parity(&cli, &mut mcp, &root, "minimal", query, *budget);
+    }
+}
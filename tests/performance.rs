@@ -0,0 +1,277 @@
+//! Performance regression baselines for `build`, `resolve` and `inspect`.
+//!
+//! Benchmark mode only: timings are too noisy for every `cargo test`, so the
+//! suite skips unless `CONTEXT_BENCH=1`. Each case runs `CONTEXT_BENCH_RUNS`
+//! times (default 5, after one warm-up) and the medians of wall time, user+sys
+//! CPU and peak RSS — taken from the child's rusage — are compared against
+//! `fixtures/v0/bench/baseline.json` within its tolerance bands. With
+//! `CONTEXT_PREV_BIN` set the same cases are also measured on the previous
+//! binary and compared side by side.
+//!
+//! `CONTEXT_BENCH_UPDATE=1` rewrites the baseline from the current binary
+//! instead of comparing; do this on the reference machine only. Until a
+//! baseline has been recorded the comparison is skipped; once it has, a case
+//! with no entry fails rather than passing uncompared.
+
+use context_compat::bench::{self, Baseline, Sample, Stats};
+use context_compat::cli_runner::CliRunner;
+use context_compat::corpus::{Corpus, CorpusSpec};
+use context_compat::fixture;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+const FIXTURE_SETS: &[&str] = &["minimal", "realistic", "tie_break", "edge_cases", "unicode"];
+const GENERATED_SEED: u64 = 20_260_301;
+const GENERATED_DOCS: usize = 2_000;
+const DEFAULT_RUNS: usize = 5;
+
+fn enabled() -> bool {
+    std::env::var("CONTEXT_BENCH").is_ok_and(|v| v == "1")
+}
+
+fn runs() -> usize {
+    match std::env::var("CONTEXT_BENCH_RUNS") {
+        Ok(s) => s
+            .parse()
+            .expect("CONTEXT_BENCH_RUNS must be a positive integer"),
+        Err(_) => DEFAULT_RUNS,
+    }
+}
+
+fn baseline_path() -> PathBuf {
+    fixture::v0_root().join("bench").join("baseline.json")
+}
+
+/// One benchmarked command line, keyed by a stable name.
+struct Case {
+    name: String,
+    args: Vec<OsString>,
+}
+
+fn args(parts: &[&dyn AsRef<std::ffi::OsStr>]) -> Vec<OsString> {
+    parts.iter().map(|p| p.as_ref().to_os_string()).collect()
+}
+
+/// All cases, with generated inputs written under `work`.
+fn cases(work: &Path) -> Vec<Case> {
+    let mut out = Vec::new();
+    let basic = fixture::query("basic");
+    let budget = basic.budget.to_string();
+    let mut sets: Vec<(String, PathBuf, PathBuf)> = FIXTURE_SETS
+        .iter()
+        .map(|s| {
+            (
+                s.to_string(),
+                fixture::documents_path(s),
+                work.join(format!("{s}_cache")),
+            )
+        })
+        .collect();
+
+    let generated = format!("generated_{GENERATED_DOCS}");
+    let sources = work.join("generated_sources");
+    Corpus::new(CorpusSpec::new(GENERATED_SEED, GENERATED_DOCS))
+        .write(&sources)
+        .expect("write generated corpus");
+    let cache = work.join("generated_cache");
+    sets.push((generated, sources, cache));
+
+    for (name, sources, cache) in &sets {
+        let scratch = work.join(format!("build_{name}"));
+        out.push(Case {
+            name: format!("build/{name}"),
+            args: args(&[
+                &"build",
+                &"--sources",
+                sources,
+                &"--cache",
+                &scratch,
+                &"--force",
+            ]),
+        });
+        out.push(Case {
+            name: format!("resolve/{name}/basic"),
+            args: args(&[
+                &"resolve",
+                &"--cache",
+                cache,
+                &"--query",
+                &basic.query,
+                &"--budget",
+                &budget,
+            ]),
+        });
+        out.push(Case {
+            name: format!("inspect/{name}"),
+            args: args(&[&"inspect", &"--cache", cache]),
+        });
+    }
+    out
+}
+
+/// Build the caches the resolve/inspect cases read from, so they do not
+/// depend on the committed fixture caches having been recorded.
+fn prepare(cli: &CliRunner, work: &Path) {
+    let sets = FIXTURE_SETS
+        .iter()
+        .map(|s| (fixture::documents_path(s), work.join(format!("{s}_cache"))))
+        .chain([(work.join("generated_sources"), work.join("generated_cache"))]);
+    for (sources, cache) in sets {
+        let out = cli
+            .build(&sources, &cache, true)
+            .expect("failed to run context build");
+        assert_eq!(
+            out.exit_code, 0,
+            "build of {} failed: {}",
+            sources.display(),
+            out.describe()
+        );
+    }
+}
+
+/// One run of `case` on `cli`, which must succeed.
+fn sample(cli: &CliRunner, case: &Case) -> Sample {
+    let (status, sample) = bench::run(cli.command().args(&case.args))
+        .unwrap_or_else(|e| panic!("{}: failed to spawn: {e}", case.name));
+    assert!(status.success(), "{}: exited with {status}", case.name);
+    sample
+}
+
+/// Median stats for `case` on `cli`, after one discarded warm-up run.
+fn measure(cli: &CliRunner, case: &Case, runs: usize) -> Stats {
+    sample(cli, case);
+    let samples: Vec<Sample> = (0..runs).map(|_| sample(cli, case)).collect();
+    bench::summarize(&samples)
+}
+
+/// Median stats for `case` on `prev` and `curr`, alternating runs so drift in
+/// machine load affects both binaries alike.
+fn measure_pair(prev: &CliRunner, curr: &CliRunner, case: &Case, runs: usize) -> (Stats, Stats) {
+    sample(prev, case);
+    sample(curr, case);
+    let mut before = Vec::with_capacity(runs);
+    let mut after = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        before.push(sample(prev, case));
+        after.push(sample(curr, case));
+    }
+    (bench::summarize(&before), bench::summarize(&after))
+}
+
+fn report(name: &str, stats: &Stats) {
+    eprintln!(
+        "bench {name}: wall {:.1}ms cpu {:.1}ms rss {}KiB",
+        stats.wall_ms, stats.cpu_ms, stats.max_rss_kb
+    );
+}
+
+/// Current binary against the committed baseline (or rewrite it).
+#[test]
+fn performance_within_baseline() {
+    if !enabled() {
+        eprintln!("CONTEXT_BENCH not set, skipping");
+        return;
+    }
+    let cli = match CliRunner::from_env() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+    let path = baseline_path();
+    let mut baseline = Baseline::load(&path).unwrap_or_else(|e| panic!("{e}"));
+    let update = std::env::var("CONTEXT_BENCH_UPDATE").is_ok_and(|v| v == "1");
+    if !update && baseline.cases.is_empty() {
+        eprintln!(
+            "{} not recorded, run with CONTEXT_BENCH_UPDATE=1 on the reference machine; skipping",
+            path.display()
+        );
+        return;
+    }
+
+    let work = tempfile::tempdir().unwrap();
+    let cases = cases(work.path());
+    prepare(&cli, work.path());
+    let runs = runs();
+
+    if update {
+        baseline.cases.clear();
+        for case in &cases {
+            let stats = measure(&cli, case, runs);
+            report(&case.name, &stats);
+            baseline.cases.insert(case.name.clone(), stats);
+        }
+        baseline.save(&path).unwrap_or_else(|e| panic!("{e}"));
+        eprintln!("baseline written to {}", path.display());
+        return;
+    }
+
+    let mut failures = Vec::new();
+    for case in &cases {
+        let stats = measure(&cli, case, runs);
+        report(&case.name, &stats);
+        match baseline.cases.get(&case.name) {
+            Some(reference) => {
+                for r in bench::regressions(&stats, reference, &baseline.tolerance) {
+                    failures.push(format!("{}: {r}", case.name));
+                }
+            }
+            None => failures.push(format!(
+                "{}: no baseline entry, record one with CONTEXT_BENCH_UPDATE=1",
+                case.name
+            )),
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "performance regressions against {}:\n{}",
+        path.display(),
+        failures.join("\n")
+    );
+}
+
+/// Current binary against `CONTEXT_PREV_BIN` on the same machine and inputs.
+#[test]
+fn performance_matches_previous_binary() {
+    if !enabled() {
+        eprintln!("CONTEXT_BENCH not set, skipping");
+        return;
+    }
+    let curr = match CliRunner::from_env() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+    let prev = match std::env::var("CONTEXT_PREV_BIN") {
+        Ok(bin) => CliRunner::new(bin),
+        Err(_) => {
+            eprintln!("CONTEXT_PREV_BIN not set, skipping");
+            return;
+        }
+    };
+    let work = tempfile::tempdir().unwrap();
+    let cases = cases(work.path());
+    prepare(&prev, work.path());
+    let runs = runs();
+    let tolerance = Baseline::load(&baseline_path())
+        .unwrap_or_else(|e| panic!("{e}"))
+        .tolerance;
+
+    let mut failures = Vec::new();
+    for case in &cases {
+        let (before, after) = measure_pair(&prev, &curr, case, runs);
+        report(&format!("{} (previous)", case.name), &before);
+        report(&format!("{} (current)", case.name), &after);
+        for r in bench::regressions(&after, &before, &tolerance) {
+            failures.push(format!("{}: {r}", case.name));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "performance regressions against previous binary:\n{}",
+        failures.join("\n")
+    );
+}
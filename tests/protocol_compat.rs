@@ -1,6 +1,7 @@
 //! MCP protocol compatibility tests: server responds correctly to JSON-RPC requests.
-//! Includes sequential stability (concurrency sanity) tests.
+//! Includes sequential stability and pipelined request (concurrency sanity) tests.
 
+use context_compat::exit_code::ExitCode;
 use context_compat::fixture;
 use context_compat::mcp_runner::McpRunner;
 
@@ -122,6 +123,12 @@ fn tools_call_list_caches() {
     let inner_text = content[0]["text"].as_str().unwrap();
     let inner: serde_json::Value = serde_json::from_str(inner_text.trim()).unwrap();
     assert!(inner["caches"].is_array());
+
+    let validator = jsonschema::validator_for(&fixture::schema("list_caches")).unwrap();
+    assert!(
+        validator.is_valid(&inner),
+        "list_caches output should validate against list_caches schema: {inner}"
+    );
 }
 
 /// tools/call for context.inspect_cache returns inspect data.
@@ -333,15 +340,24 @@ fn mcp_error_shape_frozen() {
 fn mcp_error_codes_frozen() {
     let schema = fixture::schema("mcp_error");
 
-    // These are the 6 frozen error codes from the v0 contract
-    let frozen_codes = [
-        "cache_missing",
-        "cache_invalid",
-        "invalid_query",
-        "invalid_budget",
-        "io_error",
-        "internal_error",
-    ];
+    // These are the 6 frozen error codes from the v0 contract, one per
+    // failing CLI exit code except usage errors.
+    let frozen_codes: Vec<&str> = ExitCode::mcp_codes().collect();
+    assert_eq!(
+        frozen_codes,
+        [
+            "invalid_query",
+            "invalid_budget",
+            "cache_missing",
+            "cache_invalid",
+            "io_error",
+            "internal_error",
+        ]
+    );
+    for code in &frozen_codes {
+        let exit = ExitCode::from_mcp_code(code).unwrap();
+        assert_eq!(exit.mcp_code(), Some(*code), "mapping for {code} does not round-trip");
+    }
 
     for code in &frozen_codes {
         let error_json = serde_json::json!({
@@ -372,3 +388,76 @@ fn mcp_error_codes_frozen() {
         "unknown error code should NOT validate against mcp_error schema"
     );
 }
+
+/// Pipelined requests: N `tools/call` requests written back-to-back before any
+/// response is read, mixing caches and tools. Every id gets exactly one
+/// response, identical to the sequentially obtained one, whatever the arrival order.
+#[test]
+fn pipelined_requests_match_sequential() {
+    let cache_root = fixture::cache_path("minimal").parent().unwrap().to_path_buf();
+    let mut runner = match mcp(&cache_root) {
+        Some(r) => r,
+        None => return,
+    };
+
+    runner.initialize().unwrap();
+
+    let calls = [
+        ("context.resolve", serde_json::json!({ "cache": "minimal", "query": "hello", "budget": 4000 })),
+        ("context.resolve", serde_json::json!({ "cache": "realistic", "query": "deployment security", "budget": 4000 })),
+        ("context.resolve", serde_json::json!({ "cache": "realistic", "query": "deployment", "budget": 10 })),
+        ("context.resolve", serde_json::json!({ "cache": "tie_break", "query": "deployment", "budget": 4000 })),
+        ("context.resolve", serde_json::json!({ "cache": "minimal", "query": "deployment", "budget": 0 })),
+        ("context.inspect_cache", serde_json::json!({ "cache": "minimal" })),
+        ("context.inspect_cache", serde_json::json!({ "cache": "realistic" })),
+        ("context.list_caches", serde_json::json!({})),
+        ("context.resolve", serde_json::json!({ "cache": "nonexistent_cache_xyz", "query": "test", "budget": 100 })),
+    ];
+
+    // Sequential baseline: one request, one response.
+    let mut expected = Vec::new();
+    for (name, args) in &calls {
+        let response = runner.call_tool(name, args.clone()).unwrap();
+        let v: serde_json::Value = serde_json::from_str(response.trim()).unwrap();
+        assert!(v["result"].is_object(), "sequential {name} {args} has no result: {v}");
+        expected.push(v["result"].clone());
+    }
+
+    // Pipelined: write every request before reading any response.
+    const ROUNDS: usize = 6;
+    let mut pending = std::collections::HashMap::new();
+    for round in 0..ROUNDS {
+        for (k, (name, args)) in calls.iter().enumerate() {
+            let (id, request) = runner.tool_call_request(name, args.clone());
+            runner.write(&request).unwrap();
+            pending.insert(id, (round, k));
+        }
+    }
+
+    let total = pending.len();
+    for _ in 0..total {
+        let line = runner.read_line().unwrap();
+        assert!(!line.is_empty(), "server closed stdout with {} responses outstanding", pending.len());
+        let v: serde_json::Value = serde_json::from_str(line.trim())
+            .unwrap_or_else(|e| panic!("non-JSON response line {line:?}: {e}"));
+        assert_eq!(v["jsonrpc"], "2.0");
+
+        let id = v["id"].as_u64().unwrap_or_else(|| panic!("response without numeric id: {v}"));
+        let (round, k) = pending
+            .remove(&id)
+            .unwrap_or_else(|| panic!("unexpected or duplicate response for id {id}: {v}"));
+        let (name, args) = &calls[k];
+        assert_eq!(
+            v["result"], expected[k],
+            "pipelined {name} {args} (round {round}, id {id}) differs from sequential response"
+        );
+    }
+
+    // A follow-up request must get its own response, not a stray duplicate.
+    let response = runner.list_tools().unwrap();
+    let v: serde_json::Value = serde_json::from_str(response.trim()).unwrap();
+    assert!(
+        v["result"]["tools"].is_array(),
+        "follow-up tools/list got an unexpected line: {v}"
+    );
+}
@@ -0,0 +1,177 @@
+//! Scale tests: seeded generated corpora far larger than the fixture caches.
+//!
+//! Builds and resolves corpora of `CONTEXT_SCALE_DOCS` documents (comma
+//! separated, default 1000; production-sized runs use e.g. `10000,100000`) and
+//! checks determinism, schema validity and agreement with the reference
+//! ranking in `context_compat::oracle`. Build and resolve timings are printed
+//! and appended to `scale_timings.jsonl` under the cargo target tmpdir.
+
+use context_compat::cache_check;
+use context_compat::cli_runner::CliRunner;
+use context_compat::corpus::{Corpus, CorpusSpec};
+use context_compat::fixture;
+use context_compat::invariants;
+use context_compat::oracle::{self, RankedDocument};
+use jsonschema::validator_for;
+use serde_json::Value;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+fn cli() -> Option<CliRunner> {
+    CliRunner::from_env()
+}
+
+const SEED: u64 = 20_260_209;
+const DEFAULT_SIZES: &[usize] = &[1_000];
+const QUERIES: &[&str] = &["deployment", "deployment security", "the", "rollback audit token", "xyznotfound"];
+const UNBOUNDED: usize = 1_000_000_000;
+const TIGHT: usize = 4_000;
+
+fn sizes() -> Vec<usize> {
+    match std::env::var("CONTEXT_SCALE_DOCS") {
+        Ok(s) => s
+            .split(',')
+            .map(|n| n.trim().parse().expect("CONTEXT_SCALE_DOCS must be comma-separated integers"))
+            .collect(),
+        Err(_) => DEFAULT_SIZES.to_vec(),
+    }
+}
+
+fn record_timing(documents: usize, phase: &str, query: Option<&str>, elapsed: Duration) {
+    let entry = serde_json::json!({
+        "documents": documents,
+        "phase": phase,
+        "query": query,
+        "millis": elapsed.as_secs_f64() * 1000.0,
+    });
+    eprintln!("scale timing: {entry}");
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("scale_timings.jsonl");
+    if let Ok(mut f) = std::fs::OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(f, "{entry}");
+    }
+}
+
+/// Compare a `resolve` result against the oracle ranking, document by document.
+fn assert_matches_oracle(v: &Value, expected: &[RankedDocument], ctx: &str) {
+    let docs = v["documents"].as_array().unwrap();
+    assert_eq!(docs.len(), expected.len(), "{ctx}: document count differs from oracle");
+    for (i, (actual, want)) in docs.iter().zip(expected).enumerate() {
+        assert_eq!(actual["id"], want.id.as_str(), "{ctx}: rank {i} id differs from oracle");
+        assert_eq!(
+            actual["score"].as_f64().map(|s| s as f32),
+            Some(want.score),
+            "{ctx}: score of {} differs from oracle",
+            want.id
+        );
+        assert_eq!(actual["tokens"], want.tokens, "{ctx}: tokens of {} differ", want.id);
+        assert_eq!(
+            actual["why"]["term_matches"], want.term_matches,
+            "{ctx}: term_matches of {} differ",
+            want.id
+        );
+        assert_eq!(
+            actual["why"]["total_words"], want.total_words,
+            "{ctx}: total_words of {} differ",
+            want.id
+        );
+    }
+}
+
+/// The oracle reproduces every committed resolve golden over the fixture document sets.
+#[test]
+fn oracle_matches_goldens() {
+    let cases = [
+        ("minimal", "basic", "minimal_basic"),
+        ("realistic", "basic", "realistic_basic"),
+        ("realistic", "multi_term", "realistic_multi_term"),
+        ("tie_break", "tie_break", "tie_break_ordering"),
+        ("tie_break", "no_match", "tie_break_zero_score"),
+    ];
+    for (docs_name, query_name, expected_name) in cases {
+        let dir = fixture::documents_path(docs_name);
+        let mut documents = Vec::new();
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            let id = path.file_name().unwrap().to_string_lossy().into_owned();
+            documents.push((id, std::fs::read_to_string(&path).unwrap()));
+        }
+        let q = fixture::query(query_name);
+        let ranked = oracle::rank(documents.iter().map(|(i, c)| (i.as_str(), c.as_str())), &q.query);
+        let expected: Value = serde_json::from_str(&fixture::expected(expected_name)).unwrap();
+        assert_matches_oracle(&expected, &ranked, expected_name);
+    }
+}
+
+/// Generated corpora build and resolve deterministically, validate against the
+/// schema, satisfy the selection invariants and agree with the oracle.
+#[test]
+fn scale_build_and_resolve() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let schema = fixture::schema("selection_result");
+    let validator = validator_for(&schema).unwrap();
+
+    for size in sizes() {
+        let corpus = Corpus::new(CorpusSpec::new(SEED, size));
+        let documents: Vec<(String, String)> = corpus.documents().collect();
+        let dir = tempfile::tempdir().unwrap();
+
+        // Two independent generations of the same spec must build identical caches.
+        let mut summaries = Vec::new();
+        let mut indexes = Vec::new();
+        for copy in ["a", "b"] {
+            let sources = dir.path().join(format!("sources-{copy}"));
+            let cache = dir.path().join(format!("cache-{copy}"));
+            corpus.write(&sources).unwrap();
+
+            let started = Instant::now();
+            let out = runner.build(&sources, &cache, false).unwrap();
+            record_timing(size, "build", None, started.elapsed());
+            assert_eq!(out.exit_code, 0, "{size} docs: build failed: {}", out.describe());
+
+            summaries.push(
+                cache_check::check_integrity(&cache)
+                    .unwrap_or_else(|e| panic!("{size} docs: integrity check failed: {e}")),
+            );
+            indexes.push(std::fs::read(cache.join("index.json")).unwrap());
+        }
+        assert_eq!(summaries[0], summaries[1], "{size} docs: regenerated corpus built differently");
+        assert_eq!(indexes[0], indexes[1], "{size} docs: index.json differs between builds");
+        assert_eq!(summaries[0].document_ids.len(), size);
+
+        let cache = dir.path().join("cache-a");
+        for query in QUERIES {
+            let ctx = format!("{size} docs, {query:?}");
+
+            let started = Instant::now();
+            let out1 = runner.resolve(&cache, query, UNBOUNDED).unwrap();
+            record_timing(size, "resolve", Some(query), started.elapsed());
+            let out2 = runner.resolve(&cache, query, UNBOUNDED).unwrap();
+            assert_eq!(out1.exit_code, 0, "{ctx}: resolve failed: {}", out1.describe());
+            assert_eq!(out1.stdout, out2.stdout, "{ctx}: resolve is not deterministic");
+
+            let v: Value = serde_json::from_str(out1.stdout.trim()).unwrap();
+            if let Err(e) = validator.validate(&v) {
+                panic!("{ctx}: output does not validate against selection_result: {e}");
+            }
+            invariants::check_selection(&v).unwrap_or_else(|e| panic!("{ctx}: {e}"));
+
+            let ranked = oracle::rank(documents.iter().map(|(i, c)| (i.as_str(), c.as_str())), query);
+            assert_matches_oracle(&v, &ranked, &ctx);
+
+            let out = runner.resolve(&cache, query, TIGHT).unwrap();
+            assert_eq!(out.exit_code, 0, "{ctx}: tight resolve failed: {}", out.describe());
+            let v: Value = serde_json::from_str(out.stdout.trim()).unwrap();
+            assert!(validator.is_valid(&v), "{ctx}: tight output does not validate");
+            invariants::check_selection(&v).unwrap_or_else(|e| panic!("{ctx} tight: {e}"));
+            assert_eq!(v["selection"]["documents_considered"], size);
+        }
+    }
+}
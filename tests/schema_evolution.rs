@@ -0,0 +1,255 @@
+//! Schema evolution between contract versions.
+//!
+//! The schemas of each `fixtures/vN` (see `fixture::version_schemas_root`) are
+//! checked against those of `fixtures/vN+1` with
+//! `context_compat::schema_compat`, which reports every change that breaks
+//! producers (documents valid before are now rejected) or consumers
+//! (documents they never expected are now valid).
+//!
+//! Every violation fails, with one exception: a widening that breaks only
+//! consumers, such as a new `mcp_error` code, lands once it is listed in
+//! `fixtures/vN+1/accepted_widenings.json` as
+//! `{"<schema>": ["<path>: <description>", ...]}`, copied from the failure
+//! message. Producer violations cannot be accepted, and a listed widening
+//! that is no longer reported fails so the list stays reviewed.
+
+use context_compat::fixture;
+use context_compat::schema_compat::{self, Role, Violation};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Consumer-breaking widenings accepted for the step into `fixtures/vN`.
+fn accepted_widenings(version: u32) -> BTreeMap<String, Vec<String>> {
+    let path = fixture::fixtures_root()
+        .join(format!("v{version}"))
+        .join("accepted_widenings.json");
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .unwrap_or_else(|e| panic!("failed to parse {}: {e}", path.display())),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+#[test]
+fn schemas_evolve_compatibly() {
+    let versions = fixture::contract_versions();
+    assert!(versions.contains(&0), "fixtures/v0 missing");
+    if versions.len() < 2 {
+        eprintln!("only v{} present, no schema evolution to check", versions[0]);
+    }
+
+    let mut failures = Vec::new();
+    for pair in versions.windows(2) {
+        let (old, new) = (pair[0], pair[1]);
+        let old_dir = fixture::version_schemas_root(old);
+        let new_dir = fixture::version_schemas_root(new);
+        let report = schema_compat::check_sets(
+            &schema_compat::load_dir(&old_dir).unwrap(),
+            &schema_compat::load_dir(&new_dir).unwrap(),
+        );
+        let mut accepted = accepted_widenings(new);
+        for (name, violations) in report {
+            let mut listed = accepted.remove(&name).unwrap_or_default();
+            for v in violations {
+                let key = format!("{}: {}", v.path, v.description);
+                match listed.iter().position(|l| *l == key) {
+                    Some(i) if v.role == Role::Consumer => {
+                        listed.remove(i);
+                        eprintln!("v{old} -> v{new} {name}: accepted {v}");
+                    }
+                    _ => failures.push(format!("v{old} -> v{new} {name}: {v}")),
+                }
+            }
+            accepted.insert(name, listed);
+        }
+        for (name, stale) in accepted {
+            for key in stale {
+                failures.push(format!("v{new} accepted_widenings.json {name}: {key:?} not reported"));
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "incompatible schema changes (consumer-only widenings may be listed in \
+         fixtures/vN/accepted_widenings.json):\n{}",
+        failures.join("\n")
+    );
+}
+
+/// Every frozen schema loads and is compatible with itself.
+#[test]
+fn v0_schemas_self_compatible() {
+    let schemas = schema_compat::load_dir(&fixture::version_schemas_root(0)).unwrap();
+    for name in ["cli_error", "inspect_output", "list_caches", "mcp_error", "selection_result"] {
+        assert!(schemas.contains_key(name), "{name} schema missing from v0");
+    }
+    for (name, schema) in &schemas {
+        assert_eq!(schema_compat::check(schema, schema), vec![], "{name}");
+    }
+    assert!(schema_compat::check_sets(&schemas, &schemas).is_empty());
+}
+
+// Checker self-tests, mostly on edits of the real mcp_error schema.
+
+fn roles(violations: &[Violation]) -> Vec<Role> {
+    let mut roles: Vec<Role> = violations.iter().map(|v| v.role).collect();
+    roles.sort();
+    roles.dedup();
+    roles
+}
+
+/// Apply `edit` to a copy of `schema` and assert exactly `expected` roles break.
+fn assert_breaks(schema: &Value, edit: impl FnOnce(&mut Value), expected: &[Role], what: &str) {
+    let mut next = schema.clone();
+    edit(&mut next);
+    let violations = schema_compat::check(schema, &next);
+    assert_eq!(roles(&violations), expected, "{what}: {violations:#?}");
+}
+
+fn error_code(schema: &mut Value) -> &mut Value {
+    &mut schema["properties"]["error"]["properties"]["code"]
+}
+
+#[test]
+fn enum_changes() {
+    let schema = fixture::schema("mcp_error");
+    let narrowed = |s: &mut Value| {
+        let codes = error_code(s)["enum"].as_array_mut().unwrap();
+        codes.retain(|c| c != "io_error");
+    };
+    assert_breaks(&schema, narrowed, &[Role::Producer], "enum narrowed");
+
+    let widened = |s: &mut Value| {
+        error_code(s)["enum"].as_array_mut().unwrap().push(json!("rate_limited"));
+    };
+    assert_breaks(&schema, widened, &[Role::Consumer], "enum widened");
+}
+
+#[test]
+fn required_property_changes() {
+    let schema = fixture::schema("mcp_error");
+    let mut removed = schema.clone();
+    let error = &mut removed["properties"]["error"];
+    error["properties"].as_object_mut().unwrap().remove("message");
+    error["required"] = json!(["code"]);
+    let violations = schema_compat::check(&schema, &removed);
+    assert!(roles(&violations).contains(&Role::Consumer), "removed: {violations:#?}");
+
+    let renamed = |s: &mut Value| {
+        let error = &mut s["properties"]["error"];
+        let props = error["properties"].as_object_mut().unwrap();
+        let message = props.remove("message").unwrap();
+        props.insert("detail".to_string(), message);
+        error["required"] = json!(["code", "detail"]);
+    };
+    assert_breaks(&schema, renamed, &[Role::Producer, Role::Consumer], "renamed");
+
+    let newly_required = |s: &mut Value| {
+        let error = &mut s["properties"]["error"];
+        error["properties"]["hint"] = json!({ "type": "string" });
+        error["required"] = json!(["code", "message", "hint"]);
+    };
+    // `error` is closed, so consumers never expected `hint` either.
+    let both = [Role::Producer, Role::Consumer];
+    assert_breaks(&fixture::schema("cli_error"), newly_required, &both, "newly required");
+
+    let now_optional = |s: &mut Value| {
+        s["properties"]["error"]["required"] = json!(["code"]);
+    };
+    assert_breaks(&schema, now_optional, &[Role::Consumer], "now optional");
+}
+
+fn message(schema: &mut Value) -> &mut Value {
+    &mut schema["properties"]["error"]["properties"]["message"]
+}
+
+#[test]
+fn numeric_bound_changes() {
+    let schema = fixture::schema("mcp_error");
+    assert_breaks(&schema, |s| message(s)["minLength"] = json!(8), &[Role::Producer], "minLength raised");
+    assert_breaks(&schema, |s| message(s)["maxLength"] = json!(200), &[Role::Producer], "maxLength added");
+    let relaxed = |s: &mut Value| {
+        message(s).as_object_mut().unwrap().remove("minLength");
+    };
+    assert_breaks(&schema, relaxed, &[Role::Consumer], "minLength removed");
+}
+
+#[test]
+fn additional_properties_changes() {
+    let schema = fixture::schema("mcp_error");
+    let allowed = |s: &mut Value| s["additionalProperties"] = json!(true);
+    assert_breaks(&schema, allowed, &[Role::Consumer], "now allowed");
+
+    let open = json!({ "type": "object", "properties": { "a": { "type": "string" } } });
+    let forbidden = |s: &mut Value| s["additionalProperties"] = json!(false);
+    assert_breaks(&open, forbidden, &[Role::Producer], "now forbidden");
+
+    let strings = json!({ "type": "object", "additionalProperties": { "type": "string" } });
+    let retyped = |s: &mut Value| s["additionalProperties"] = json!({ "type": "integer" });
+    assert_breaks(&strings, retyped, &[Role::Producer, Role::Consumer], "schema retyped");
+    let constrained = |s: &mut Value| s["additionalProperties"]["maxLength"] = json!(64);
+    assert_breaks(&strings, constrained, &[Role::Producer], "schema constrained");
+    let unconstrained = |s: &mut Value| s["additionalProperties"] = json!(true);
+    assert_breaks(&strings, unconstrained, &[Role::Consumer], "schema dropped");
+}
+
+#[test]
+fn optional_property_changes() {
+    let closed = fixture::schema("mcp_error");
+    let hint = |s: &mut Value| s["properties"]["error"]["properties"]["hint"] = json!({ "type": "string" });
+    assert_breaks(&closed, hint, &[Role::Consumer], "added to a closed object");
+    let open = json!({ "type": "object", "properties": { "a": { "type": "string" } } });
+    let added = |s: &mut Value| s["properties"]["b"] = json!({ "type": "string" });
+    assert_breaks(&open, added, &[], "added to an open object");
+
+    let removed = |s: &mut Value| {
+        s["properties"].as_object_mut().unwrap().remove("a");
+    };
+    assert_breaks(&open, removed, &[Role::Consumer], "removed from an open object");
+    let mut closed = open.clone();
+    closed["additionalProperties"] = json!(false);
+    assert_breaks(&closed, removed, &[Role::Producer], "removed from a closed object");
+    let mut strings = open.clone();
+    strings["additionalProperties"] = json!({ "type": "string" });
+    assert_breaks(&strings, removed, &[], "removed into a matching additionalProperties");
+}
+
+#[test]
+fn unmodelled_keyword_changes() {
+    let schema = fixture::schema("mcp_error");
+    let pinned = |s: &mut Value| message(s)["const"] = json!("failed");
+    assert_breaks(&schema, pinned, &[Role::Producer], "const added");
+    let stepped = |s: &mut Value| s["properties"]["error"]["multipleOf"] = json!(2);
+    assert_breaks(&schema, stepped, &[Role::Producer], "multipleOf added");
+
+    let mut pinned_schema = schema.clone();
+    message(&mut pinned_schema)["const"] = json!("failed");
+    let unpinned = |s: &mut Value| {
+        message(s).as_object_mut().unwrap().remove("const");
+    };
+    assert_breaks(&pinned_schema, unpinned, &[Role::Consumer], "const removed");
+    let repinned = |s: &mut Value| message(s)["const"] = json!("aborted");
+    let both = [Role::Producer, Role::Consumer];
+    assert_breaks(&pinned_schema, repinned, &both, "const changed");
+}
+
+#[test]
+fn identity_and_documentation_changes_are_compatible() {
+    let schema = fixture::schema("mcp_error");
+    let relabelled = |s: &mut Value| {
+        s["$id"] = json!("https://context.dev/schemas/mcp/error-v1.json");
+        s["title"] = json!("MCP Error Response v1");
+        s["description"] = json!("Error returned inside a tool result");
+    };
+    assert_breaks(&schema, relabelled, &[], "relabelled");
+}
+
+#[test]
+fn removed_schema_breaks_both_roles() {
+    let old = schema_compat::load_dir(&fixture::schemas_root()).unwrap();
+    let mut new = old.clone();
+    new.remove("list_caches");
+    let report = schema_compat::check_sets(&old, &new);
+    assert_eq!(report.keys().collect::<Vec<_>>(), vec!["list_caches"]);
+    assert_eq!(roles(&report["list_caches"]), vec![Role::Producer, Role::Consumer]);
+}
@@ -45,7 +45,7 @@ fn resolve_validates_schema() {
         assert_eq!(
             out.exit_code, 0,
             "resolve failed for {cache_name}/{query_name}: {}",
-            out.stderr
+            out.describe()
         );
 
         let value: serde_json::Value = serde_json::from_str(out.stdout.trim())
@@ -75,7 +75,7 @@ fn inspect_validates_schema() {
         assert_eq!(
             out.exit_code, 0,
             "inspect failed for {cache_name}: {}",
-            out.stderr
+            out.describe()
         );
 
         let value: serde_json::Value = serde_json::from_str(out.stdout.trim())
@@ -128,7 +128,7 @@ fn freshly_built_cache_validates() {
     let cache = dir.path().join("fresh");
 
     let build_out = runner.build(&sources, &cache, false).unwrap();
-    assert_eq!(build_out.exit_code, 0, "build failed: {}", build_out.stderr);
+    assert_eq!(build_out.exit_code, 0, "build failed: {}", build_out.describe());
 
     // Inspect
     let inspect_out = runner.inspect(&cache).unwrap();
@@ -0,0 +1,105 @@
+//! Property-based tests: selection invariants over generated corpora.
+//!
+//! Each case builds a random corpus with `CliRunner::build`, resolves a random
+//! query and budget against it, and checks the invariants in
+//! `context_compat::invariants`. Failing cases are persisted to
+//! `fixtures/v0/regressions/selection_properties.txt` and replayed first on
+//! every later run.
+
+use context_compat::cli_runner::CliRunner;
+use context_compat::invariants;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, FileFailurePersistence, TestCaseError, TestRunner};
+use std::collections::BTreeMap;
+
+fn cli() -> Option<CliRunner> {
+    CliRunner::from_env()
+}
+
+const CASES: u32 = 48;
+const REGRESSIONS: &str = "fixtures/v0/regressions/selection_properties.txt";
+
+/// Words documents are built from: mixed case, punctuation and a term that never
+/// appears in queries, so scores span 0 to 1.
+const DOC_WORDS: &[&str] = &[
+    "deployment", "Deployment", "DEPLOYMENT", "security", "Security", "guide", "guide.",
+    "rollback", "alpha", "bravo", "zeta",
+];
+
+/// Query terms: most overlap with `DOC_WORDS`, one never matches.
+const QUERY_WORDS: &[&str] = &["deployment", "security", "guide", "rollback", "xyznotfound"];
+
+#[derive(Debug, Clone)]
+struct Case {
+    documents: BTreeMap<String, String>,
+    query: String,
+    budget: usize,
+}
+
+fn case() -> impl Strategy<Value = Case> {
+    let content = prop::collection::vec(prop::sample::select(DOC_WORDS), 0..40)
+        .prop_map(|words| words.join(" "));
+    let documents = prop::collection::btree_map("[a-z]{1,8}\\.md", content, 1..8);
+    let query = prop::collection::vec(prop::sample::select(QUERY_WORDS), 0..4)
+        .prop_map(|terms| terms.join(" "));
+    (documents, query, 0usize..300).prop_map(|(documents, query, budget)| Case {
+        documents,
+        query,
+        budget,
+    })
+}
+
+fn config() -> Config {
+    Config {
+        cases: CASES,
+        failure_persistence: Some(Box::new(FileFailurePersistence::Direct(REGRESSIONS))),
+        ..Config::default()
+    }
+}
+
+fn check(runner: &CliRunner, case: &Case) -> Result<(), TestCaseError> {
+    let dir = tempfile::tempdir().unwrap();
+    let sources = dir.path().join("sources");
+    let cache = dir.path().join("cache");
+    std::fs::create_dir(&sources).unwrap();
+    for (id, content) in &case.documents {
+        std::fs::write(sources.join(id), content).unwrap();
+    }
+
+    let build = runner.build(&sources, &cache, false).unwrap();
+    prop_assert_eq!(build.exit_code, 0, "build failed: {}", build.describe());
+
+    let out1 = runner.resolve(&cache, &case.query, case.budget).unwrap();
+    let out2 = runner.resolve(&cache, &case.query, case.budget).unwrap();
+    prop_assert_eq!(out1.exit_code, 0, "resolve failed: {}", out1.describe());
+    prop_assert_eq!(&out1.stdout, &out2.stdout, "resolve output is not deterministic");
+
+    let v: serde_json::Value = serde_json::from_str(out1.stdout.trim())
+        .map_err(|e| TestCaseError::fail(format!("invalid JSON from resolve: {e}")))?;
+    invariants::check_selection(&v).map_err(TestCaseError::fail)?;
+
+    prop_assert_eq!(
+        &v["selection"]["documents_considered"],
+        &serde_json::json!(case.documents.len()),
+        "every document in the corpus must be considered"
+    );
+    prop_assert_eq!(&v["selection"]["budget"], &serde_json::json!(case.budget));
+    Ok(())
+}
+
+/// Invariants hold for every generated corpus, query and budget.
+#[test]
+fn selection_invariants_hold() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let mut tests = TestRunner::new(config());
+    if let Err(e) = tests.run(&case(), |case| check(&runner, &case)) {
+        panic!("selection invariant violated: {e}");
+    }
+}
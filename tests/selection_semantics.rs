@@ -30,7 +30,7 @@ fn equal_score_ordered_by_id() {
     let q = fixture::query("tie_break");
     let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
 
-    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.stderr);
+    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.describe());
 
     let v: serde_json::Value = serde_json::from_str(out.stdout.trim()).unwrap();
     let docs = v["documents"].as_array().unwrap();
@@ -66,7 +66,7 @@ fn zero_score_documents_included() {
     let q = fixture::query("no_match");
     let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
 
-    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.stderr);
+    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.describe());
 
     let v: serde_json::Value = serde_json::from_str(out.stdout.trim()).unwrap();
     let docs = v["documents"].as_array().unwrap();
@@ -113,7 +113,7 @@ fn zero_budget_excludes_all() {
     let q = fixture::query("zero_budget");
     let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
 
-    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.stderr);
+    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.describe());
 
     let v: serde_json::Value = serde_json::from_str(out.stdout.trim()).unwrap();
     let docs = v["documents"].as_array().unwrap();
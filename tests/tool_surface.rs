@@ -0,0 +1,285 @@
+//! The full MCP tool surface, frozen.
+//!
+//! The `tools/list` result (names, descriptions, `inputSchema`, `outputSchema`
+//! and annotations) must equal `fixtures/v0/expected/tools_list.json`, with
+//! tools sorted by name. On mismatch every difference is classified by
+//! `context_compat::surface` as additive or breaking, so accepting a surface
+//! change with `CONTEXT_SURFACE_UPDATE=1` (or `make fixtures-surface`) is a
+//! deliberate, reviewed decision.
+
+use context_compat::fixture;
+use context_compat::mcp_runner::McpRunner;
+use context_compat::surface::{self, Severity};
+use serde_json::{json, Value};
+
+fn golden_path() -> std::path::PathBuf {
+    fixture::v0_root().join("expected").join("tools_list.json")
+}
+
+fn tools_list() -> Option<Value> {
+    let cache_root = fixture::v0_root().join("caches");
+    let mut runner = match McpRunner::from_env(&cache_root) {
+        Some(Ok(runner)) => runner,
+        Some(Err(e)) => panic!("failed to spawn MCP server: {e}"),
+        None => {
+            eprintln!("MCP_SERVER_BIN not set, skipping");
+            return None;
+        }
+    };
+    runner.initialize().unwrap();
+    let response = runner.list_tools().unwrap();
+    let v: Value = serde_json::from_str(response.trim()).unwrap();
+    assert!(v["result"]["tools"].is_array(), "tools/list has no tools: {v}");
+    Some(surface::normalize(&v["result"]))
+}
+
+#[test]
+fn tool_surface_matches_golden() {
+    let actual = match tools_list() {
+        Some(v) => v,
+        None => return,
+    };
+    if std::env::var("CONTEXT_SURFACE_UPDATE").as_deref() == Ok("1") {
+        let json = serde_json::to_string_pretty(&actual).unwrap();
+        std::fs::write(golden_path(), json + "\n").unwrap();
+        eprintln!("updated {}", golden_path().display());
+        return;
+    }
+
+    fixture::require_recorded(&golden_path(), "fixtures-surface");
+    let expected: Value = serde_json::from_str(&fixture::expected("tools_list")).unwrap();
+    if actual == expected {
+        return;
+    }
+    let changes = surface::diff(&expected, &actual);
+    let verdict = if surface::has_breaking(&changes) {
+        "BREAKING tool surface change"
+    } else {
+        "additive tool surface change"
+    };
+    let listed: Vec<String> = changes.iter().map(|c| format!("  {c}")).collect();
+    panic!(
+        "{verdict} ({} changes):\n{}\nIf intentional, review and rerun with CONTEXT_SURFACE_UPDATE=1.",
+        changes.len(),
+        listed.join("\n")
+    );
+}
+
+// Classifier self-tests on a synthetic surface.
+
+fn base() -> Value {
+    json!({ "tools": [{
+        "name": "context.resolve",
+        "description": "Resolve context from a cache",
+        "inputSchema": {
+            "type": "object",
+            "required": ["cache", "query"],
+            "properties": {
+                "cache": { "type": ["string", "null"] },
+                "query": { "type": "string" },
+                "budget": { "type": "integer", "minimum": 0 },
+                "mode": { "type": "string", "enum": ["fast", "exact"] }
+            }
+        },
+        "outputSchema": {
+            "type": "object",
+            "required": ["documents"],
+            "properties": {
+                "documents": { "type": "array", "items": { "type": "object" } },
+                "score": { "type": "integer" }
+            }
+        },
+        "annotations": { "readOnlyHint": true }
+    }]})
+}
+
+type Edit = fn(&mut Value);
+
+/// Apply `edit` to the first tool of [`base`] and classify the result.
+fn classify(edit: impl FnOnce(&mut Value)) -> Vec<(Severity, String)> {
+    let mut changed = base();
+    edit(&mut changed["tools"][0]);
+    surface::diff(&base(), &changed)
+        .into_iter()
+        .map(|c| (c.severity, format!("{}: {}", c.path, c.description)))
+        .collect()
+}
+
+fn assert_only(changes: &[(Severity, String)], severity: Severity, what: &str) {
+    assert!(!changes.is_empty(), "{what}: no change detected");
+    assert!(
+        changes.iter().all(|(s, _)| *s == severity),
+        "{what}: expected only {severity} changes, got {changes:?}"
+    );
+}
+
+#[test]
+fn identical_surfaces_have_no_changes() {
+    assert!(surface::diff(&base(), &base()).is_empty());
+    let mut reordered = base();
+    reordered["tools"]
+        .as_array_mut()
+        .unwrap()
+        .insert(0, json!({ "name": "context.zzz", "inputSchema": { "type": "object" } }));
+    assert_eq!(surface::normalize(&reordered)["tools"][0]["name"], "context.resolve");
+}
+
+#[test]
+fn additive_changes_classified() {
+    let mut with_tool = base();
+    with_tool["tools"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({ "name": "context.new", "inputSchema": { "type": "object" } }));
+    let changes = surface::diff(&base(), &with_tool);
+    assert_eq!(changes.len(), 1, "{changes:?}");
+    assert_eq!(changes[0].severity, Severity::Additive);
+
+    let cases: Vec<(&str, Edit)> = vec![
+        ("new optional input", |t| {
+            t["inputSchema"]["properties"]["limit"] = json!({ "type": "integer" });
+        }),
+        ("input no longer required", |t| {
+            t["inputSchema"]["required"] = json!(["cache"]);
+        }),
+        ("input type widened", |t| {
+            t["inputSchema"]["properties"]["budget"]["type"] = json!("number");
+        }),
+        ("input enum value added", |t| {
+            t["inputSchema"]["properties"]["mode"]["enum"] = json!(["fast", "exact", "auto"]);
+        }),
+        ("input minimum relaxed", |t| {
+            t["inputSchema"]["properties"]["budget"]
+                .as_object_mut()
+                .unwrap()
+                .remove("minimum");
+        }),
+        ("new output field", |t| {
+            t["outputSchema"]["properties"]["took_ms"] = json!({ "type": "number" });
+        }),
+        ("description reworded", |t| {
+            t["description"] = json!("Resolve context from a named cache");
+        }),
+        ("annotation title added", |t| {
+            t["annotations"]["title"] = json!("Resolve");
+        }),
+        ("now idempotent", |t| {
+            t["annotations"]["idempotentHint"] = json!(true);
+        }),
+    ];
+    for (what, edit) in cases {
+        assert_only(&classify(edit), Severity::Additive, what);
+    }
+}
+
+#[test]
+fn breaking_changes_classified() {
+    let mut without_tool = base();
+    without_tool["tools"] = json!([]);
+    let changes = surface::diff(&base(), &without_tool);
+    assert_eq!(changes.len(), 1, "{changes:?}");
+    assert_eq!(changes[0].severity, Severity::Breaking);
+
+    let cases: Vec<(&str, Edit)> = vec![
+        ("new required input", |t| {
+            t["inputSchema"]["properties"]["tenant"] = json!({ "type": "string" });
+            t["inputSchema"]["required"] = json!(["cache", "query", "tenant"]);
+        }),
+        ("existing input now required", |t| {
+            t["inputSchema"]["required"] = json!(["cache", "query", "budget"]);
+        }),
+        ("input type narrowed", |t| {
+            t["inputSchema"]["properties"]["cache"]["type"] = json!("string");
+        }),
+        ("input enum value removed", |t| {
+            t["inputSchema"]["properties"]["mode"]["enum"] = json!(["fast"]);
+        }),
+        ("input minimum raised", |t| {
+            t["inputSchema"]["properties"]["budget"]["minimum"] = json!(1);
+        }),
+        ("input renamed", |t| {
+            let props = t["inputSchema"]["properties"].as_object_mut().unwrap();
+            let budget = props.remove("budget").unwrap();
+            props.insert("token_budget".to_string(), budget);
+        }),
+        ("extra inputs rejected", |t| {
+            t["inputSchema"]["additionalProperties"] = json!(false);
+        }),
+        ("output field removed", |t| {
+            t["outputSchema"]["properties"].as_object_mut().unwrap().remove("score");
+        }),
+        ("output field renamed", |t| {
+            let props = t["outputSchema"]["properties"].as_object_mut().unwrap();
+            let docs = props.remove("documents").unwrap();
+            props.insert("docs".to_string(), docs);
+            t["outputSchema"]["required"] = json!(["docs"]);
+        }),
+        ("output type widened", |t| {
+            t["outputSchema"]["properties"]["score"]["type"] = json!("number");
+        }),
+        ("output field no longer always present", |t| {
+            t["outputSchema"]["required"] = json!([]);
+        }),
+        ("output schema removed", |t| {
+            t.as_object_mut().unwrap().remove("outputSchema");
+        }),
+        ("no longer read-only", |t| {
+            t["annotations"]["readOnlyHint"] = json!(false);
+        }),
+    ];
+    for (what, edit) in cases {
+        let changes = classify(edit);
+        assert!(
+            changes.iter().any(|(s, _)| *s == Severity::Breaking),
+            "{what}: not classified as breaking: {changes:?}"
+        );
+    }
+}
+
+/// A schema-valued `additionalProperties` is compared as a schema, and
+/// switching between a schema and `true`/`false` widens or narrows.
+#[test]
+fn additional_properties_schema_changes() {
+    // Extra inputs, starting from "any extra input must be a string".
+    let extra = |from: Value, to: Value| {
+        let mut old = base();
+        old["tools"][0]["inputSchema"]["additionalProperties"] = from;
+        let mut new = old.clone();
+        new["tools"][0]["inputSchema"]["additionalProperties"] = to;
+        surface::diff(&old, &new)
+    };
+    let strings = || json!({ "type": "string" });
+
+    let breaking = [
+        ("retyped", strings(), json!({ "type": "integer" })),
+        ("now forbidden", strings(), json!(false)),
+        ("now constrained", json!(true), strings()),
+    ];
+    for (what, from, to) in breaking {
+        let changes = extra(from, to);
+        assert!(surface::has_breaking(&changes), "{what}: {changes:?}");
+        assert!(changes[0].path.ends_with("inputSchema.additionalProperties"), "{what}: {changes:?}");
+    }
+
+    let additive = [
+        ("type widened", strings(), json!({ "type": ["string", "null"] })),
+        ("now unconstrained", strings(), json!({})),
+        ("now allowed", json!(false), strings()),
+    ];
+    for (what, from, to) in additive {
+        let changes = extra(from, to);
+        assert!(!changes.is_empty(), "{what}: no change detected");
+        assert!(!surface::has_breaking(&changes), "{what}: {changes:?}");
+    }
+}
+
+#[test]
+fn rename_reported_once() {
+    let changes = classify(|t| {
+        let props = t["inputSchema"]["properties"].as_object_mut().unwrap();
+        let budget = props.remove("budget").unwrap();
+        props.insert("token_budget".to_string(), budget);
+    });
+    assert_eq!(changes.len(), 1, "{changes:?}");
+    assert!(changes[0].1.contains("renamed to token_budget"), "{changes:?}");
+}
@@ -0,0 +1,280 @@
+//! Record-and-replay of MCP session transcripts.
+//!
+//! Every `fixtures/v0/transcripts/*.jsonl` is replayed against a fresh server
+//! with `CONTEXT_CACHE_ROOT=fixtures/v0/caches`: recorded `send` messages are
+//! written in order and each recorded `recv` message is compared with the
+//! next line the server prints, with JSON tool-result text parsed and volatile
+//! fields redacted (see `context_compat::transcript`). A bug report becomes a
+//! regression fixture by dropping its transcript into that directory.
+//!
+//! `CONTEXT_TRANSCRIPT_UPDATE=1` re-records the responses from the current
+//! binary, keeping the sends and the redaction header. A transcript holding
+//! only `send` lines is a valid starting point, but fails replay until its
+//! responses have been recorded.
+
+use context_compat::fixture;
+use context_compat::mcp_runner::McpRunner;
+use context_compat::transcript::{self, Entry, Transcript};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn transcripts_dir() -> PathBuf {
+    fixture::v0_root().join("transcripts")
+}
+
+fn mcp(cache_root: &Path) -> Option<McpRunner> {
+    match McpRunner::from_env(cache_root) {
+        Some(Ok(runner)) => Some(runner),
+        Some(Err(e)) => panic!("failed to spawn MCP server: {e}"),
+        None => {
+            eprintln!("MCP_SERVER_BIN not set, skipping");
+            None
+        }
+    }
+}
+
+fn read(runner: &mut McpRunner, ctx: &str) -> Result<Value, String> {
+    match runner.read_line_timeout(TIMEOUT) {
+        Ok(Some(line)) if line.is_empty() => Err(format!("{ctx}: server closed stdout")),
+        Ok(Some(line)) => Ok(transcript::message(&line)),
+        Ok(None) => Err(format!("{ctx}: no response within {TIMEOUT:?}")),
+        Err(e) => Err(format!("{ctx}: {e}")),
+    }
+}
+
+/// Replay `t` and return the first divergence, message by message.
+fn replay(runner: &mut McpRunner, t: &Transcript) -> Result<(), String> {
+    let pointers = t.redactions();
+    for (n, entry) in t.entries.iter().enumerate() {
+        let ctx = format!("entry {}", n + 1);
+        match entry {
+            Entry::Send(_) | Entry::SendBase64(_) => runner
+                .write_bytes(&entry.sent_bytes().ok_or(format!("{ctx}: invalid base64"))?)
+                .map_err(|e| format!("{ctx}: write failed: {e}"))?,
+            Entry::Recv(expected) => {
+                let actual = read(runner, &ctx)?;
+                let (actual, expected) = (
+                    transcript::redact(&transcript::expand(&actual), &pointers),
+                    transcript::redact(&transcript::expand(expected), &pointers),
+                );
+                if actual != expected {
+                    return Err(format!(
+                        "{ctx}: response differs\n  actual:   {actual}\n  expected: {expected}"
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Re-record responses: after each request, read until its response arrives.
+/// Notifications (no `id`) get no response.
+fn rerecord(runner: &mut McpRunner, t: &Transcript) -> Result<Transcript, String> {
+    let mut fresh = Transcript {
+        redact: t.redact.clone(),
+        entries: Vec::new(),
+    };
+    for (n, entry) in t.entries.iter().enumerate() {
+        let Some(bytes) = entry.sent_bytes() else { continue };
+        fresh.entries.push(entry.clone());
+        runner
+            .write_bytes(&bytes)
+            .map_err(|e| format!("entry {}: write failed: {e}", n + 1))?;
+        let id = match serde_json::from_slice::<Value>(&bytes) {
+            Ok(message) => match message.get("id") {
+                Some(id) => id.clone(),
+                None => continue,
+            },
+            // Unparseable input still gets an error response with a null id.
+            Err(_) => Value::Null,
+        };
+        loop {
+            let received = read(runner, &format!("entry {}", n + 1))?;
+            let done = received.get("id") == Some(&id);
+            fresh.entries.push(Entry::Recv(received));
+            if done {
+                break;
+            }
+        }
+    }
+    Ok(fresh)
+}
+
+fn transcripts() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(transcripts_dir()) {
+        Ok(rd) => rd
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "jsonl"))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths
+}
+
+/// Every committed transcript replays identically on a fresh server.
+#[test]
+fn transcripts_replay() {
+    if std::env::var("MCP_SERVER_BIN").is_err() {
+        eprintln!("MCP_SERVER_BIN not set, skipping");
+        return;
+    }
+    let update = std::env::var("CONTEXT_TRANSCRIPT_UPDATE").as_deref() == Ok("1");
+    let cache_root = fixture::v0_root().join("caches");
+    let paths = transcripts();
+    assert!(!paths.is_empty(), "no transcripts in {}", transcripts_dir().display());
+
+    let mut failures = Vec::new();
+    for path in &paths {
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let t = Transcript::load(path).unwrap();
+        let mut runner = mcp(&cache_root).unwrap();
+        if update {
+            let fresh = rerecord(&mut runner, &t).unwrap_or_else(|e| panic!("{name}: {e}"));
+            fresh.save(path).unwrap();
+            eprintln!("re-recorded {name}");
+        } else if !t.entries.iter().any(|e| matches!(e, Entry::Recv(_))) {
+            failures.push(format!(
+                "{name}: no recorded responses, record them from the real server with \
+                 CONTEXT_TRANSCRIPT_UPDATE=1"
+            ));
+        } else if let Err(e) = replay(&mut runner, &t) {
+            failures.push(format!("{name}: {e}"));
+        }
+    }
+    assert!(failures.is_empty(), "transcript replay failed:\n{}", failures.join("\n"));
+}
+
+/// A session recorded through `McpRunner::record_to` replays against a new server.
+#[test]
+fn recorded_session_replays() {
+    let cache_root = fixture::v0_root().join("caches");
+    let mut runner = match mcp(&cache_root) {
+        Some(r) => r,
+        None => return,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.jsonl");
+    runner.record_to(&path).unwrap();
+
+    runner.initialize().unwrap();
+    runner.list_tools().unwrap();
+    runner.call_tool("context.list_caches", json!({})).unwrap();
+    runner
+        .call_tool(
+            "context.resolve",
+            json!({ "cache": "minimal", "query": "deployment", "budget": 4000 }),
+        )
+        .unwrap();
+    runner
+        .call_tool("context.inspect_cache", json!({ "cache": "missing" }))
+        .unwrap();
+    runner.send_unknown_method().unwrap();
+    drop(runner);
+
+    let t = Transcript::load(&path).unwrap();
+    assert_eq!(t.entries.len(), 12, "expected 6 requests and 6 responses: {t:?}");
+    for pair in t.entries.chunks(2) {
+        let (Entry::Send(request), Entry::Recv(response)) = (&pair[0], &pair[1]) else {
+            panic!("entries out of order: {pair:?}");
+        };
+        assert_eq!(request["id"], response["id"], "response does not answer request");
+    }
+
+    let mut fresh = mcp(&cache_root).unwrap();
+    if let Err(e) = replay(&mut fresh, &t) {
+        panic!("recorded session does not replay: {e}");
+    }
+}
+
+/// Redaction masks present pointers on both sides and ignores absent ones.
+#[test]
+fn redaction_masks_volatile_fields() {
+    let a = json!({ "id": 1, "result": { "serverInfo": { "name": "s", "version": "1.2.3" } } });
+    let b = json!({ "id": 1, "result": { "serverInfo": { "name": "s", "version": "1.3.0" } } });
+    let t = Transcript::default();
+    assert_ne!(a, b);
+    assert_eq!(transcript::redact(&a, &t.redactions()), transcript::redact(&b, &t.redactions()));
+
+    let error = json!({ "id": 2, "error": { "code": -32601, "message": "unknown" } });
+    assert_eq!(transcript::redact(&error, &["/result/serverInfo/version"]), error);
+
+    let custom = Transcript {
+        redact: vec!["/error/message".to_string()],
+        entries: Vec::new(),
+    };
+    let redacted = transcript::redact(&error, &custom.redactions());
+    assert_eq!(redacted["error"]["message"], transcript::REDACTED);
+    assert_eq!(redacted["error"]["code"], -32601);
+}
+
+/// Tool-result JSON text compares by value, and pointers reach inside it.
+#[test]
+fn tool_result_text_compared_as_json() {
+    let response = |text: &str| {
+        json!({ "id": 3, "result": { "content": [{ "type": "text", "text": text }] } })
+    };
+    let a = response(r#"{"score":0.5,"selection":{"took_ms":12}}"#);
+    let b = response("{\"selection\": {\"took_ms\": 40}, \"score\": 5e-1}\n");
+    assert_ne!(transcript::expand(&a), transcript::expand(&b));
+
+    let pointers = ["/result/content/0/text/selection/took_ms"];
+    assert_eq!(
+        transcript::redact(&transcript::expand(&a), &pointers),
+        transcript::redact(&transcript::expand(&b), &pointers)
+    );
+
+    let plain = response("not json");
+    assert_eq!(transcript::expand(&plain), plain);
+}
+
+/// Transcripts round-trip through save and load, including raw non-JSON lines.
+#[test]
+fn transcript_file_round_trip() {
+    let t = Transcript {
+        redact: vec!["/result/content/0/text".to_string()],
+        entries: vec![
+            Entry::sent(br#"{"id":1,"jsonrpc":"2.0","method":"ping"}"#),
+            Entry::Recv(json!({ "jsonrpc": "2.0", "id": 1, "result": {} })),
+            Entry::sent(b"{not json"),
+            Entry::sent(b"\xff\xfe"),
+        ],
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("t.jsonl");
+    t.save(&path).unwrap();
+    assert_eq!(Transcript::load(&path).unwrap(), t);
+    assert!(matches!(t.entries[0], Entry::Send(Value::Object(_))), "{t:?}");
+}
+
+/// Sends replay byte for byte, whatever re-serializing them would change.
+#[test]
+fn sent_lines_kept_verbatim() {
+    let lines: &[&[u8]] = &[
+        br#"{"id":1,"jsonrpc":"2.0","method":"ping"}"#,
+        br#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#,
+        br#"{"jsonrpc": "2.0", "id": 1, "method": "ping"}"#,
+        br#"{"id":1,"id":2,"jsonrpc":"2.0","method":"ping"}"#,
+        br#"{"id":1.0e0,"jsonrpc":"2.0","method":"ping"}"#,
+        br#""a bare string""#,
+        b"{not json",
+        b"",
+        b"{\"method\":\"\xff\xfe\"}",
+    ];
+    for line in lines {
+        let entry = Entry::sent(line);
+        let reloaded: Entry =
+            serde_json::from_str(&serde_json::to_string(&entry).unwrap()).unwrap();
+        assert_eq!(
+            reloaded.sent_bytes().as_deref(),
+            Some(*line),
+            "{:?} not kept verbatim as {entry:?}",
+            String::from_utf8_lossy(line)
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Unicode and tokenization conformance: goldens over `fixtures/v0/documents/unicode`.
+//!
+//! Locks `why.total_words`, `why.term_matches` and `why.query_terms` for text where
+//! tokenizers commonly disagree: NFC vs NFD, Turkish dotted/dotless i, CJK without
+//! spaces, emoji and ZWJ sequences, combining marks, full-width digits,
+//! punctuation-adjacent terms and non-breaking spaces. Goldens come from `make
+//! fixtures`; the consistency and round-trip checks build their own cache.
+
+use context_compat::cli_runner::CliRunner;
+use context_compat::fixture;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+fn cli() -> Option<CliRunner> {
+    CliRunner::from_env()
+}
+
+/// Query fixtures resolved against the `unicode` cache, each with a golden of
+/// the same name. Keep in sync with `UNICODE_QUERIES` in the Makefile.
+const UNICODE_QUERIES: &[&str] = &[
+    "unicode_nfc",
+    "unicode_nfd",
+    "unicode_turkish_lower",
+    "unicode_turkish_dotted",
+    "unicode_turkish_dotless",
+    "unicode_cjk",
+    "unicode_emoji",
+    "unicode_zwj",
+    "unicode_combining",
+    "unicode_fullwidth_digits",
+    "unicode_fullwidth_digits_wide",
+    "unicode_punctuation",
+    "unicode_nbsp",
+];
+
+/// Build `fixtures/v0/documents/unicode` into a fresh cache, so checks that
+/// need no golden do not depend on the committed one being recorded.
+fn build_unicode(runner: &CliRunner, dir: &Path) -> PathBuf {
+    let cache = dir.join("unicode");
+    let out = runner.build(&fixture::documents_path("unicode"), &cache, false).unwrap();
+    assert_eq!(out.exit_code, 0, "build failed: {}", out.describe());
+    cache
+}
+
+fn resolve(runner: &CliRunner, cache: &Path, name: &str) -> Value {
+    let q = fixture::query(name);
+    let out = runner.resolve(cache, &q.query, q.budget).unwrap();
+    assert_eq!(out.exit_code, 0, "resolve {name} failed: {}", out.describe());
+    serde_json::from_str(out.stdout.trim()).unwrap()
+}
+
+/// `why` object per document id.
+fn why_by_id(v: &Value) -> BTreeMap<String, Value> {
+    v["documents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| (d["id"].as_str().unwrap().to_string(), d["why"].clone()))
+        .collect()
+}
+
+/// Every unicode query reproduces the golden `why` fields document by document,
+/// then the full golden output.
+#[test]
+fn unicode_why_fields_match_goldens() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let cache = fixture::require_cache("unicode");
+
+    for name in UNICODE_QUERIES {
+        let q = fixture::query(name);
+        let out = runner.resolve(&cache, &q.query, q.budget).unwrap();
+        assert_eq!(out.exit_code, 0, "resolve {name} failed: {}", out.describe());
+
+        let expected_raw = fixture::require_expected(name);
+        let expected: Value = serde_json::from_str(&expected_raw).unwrap();
+        let actual: Value = serde_json::from_str(out.stdout.trim()).unwrap();
+
+        let expected_why = why_by_id(&expected);
+        let actual_why = why_by_id(&actual);
+        assert_eq!(
+            actual_why.keys().collect::<Vec<_>>(),
+            expected_why.keys().collect::<Vec<_>>(),
+            "{name}: selected documents differ"
+        );
+        for (id, want) in &expected_why {
+            let got = &actual_why[id];
+            for field in ["query_terms", "term_matches", "total_words"] {
+                assert_eq!(
+                    got[field], want[field],
+                    "{name} ({:?}): why.{field} differs for {id}",
+                    q.query
+                );
+            }
+        }
+
+        assert_eq!(
+            fixture::canonicalize(&out.stdout),
+            expected_raw,
+            "{name}: golden output mismatch"
+        );
+    }
+}
+
+/// Non-ASCII content and ids round-trip byte-for-byte through build and resolve.
+#[test]
+fn unicode_content_round_trips() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache = build_unicode(&runner, dir.path());
+    let sources = fixture::documents_path("unicode");
+    let out = runner.resolve(&cache, "", 1_000_000).unwrap();
+    assert_eq!(out.exit_code, 0, "resolve failed: {}", out.describe());
+    let v: Value = serde_json::from_str(out.stdout.trim()).unwrap();
+
+    let docs = v["documents"].as_array().unwrap();
+    assert_eq!(docs.len(), std::fs::read_dir(&sources).unwrap().count());
+    for doc in docs {
+        let id = doc["id"].as_str().unwrap();
+        let source = std::fs::read_to_string(sources.join(id)).unwrap();
+        assert_eq!(
+            doc["content"].as_str().unwrap(),
+            source,
+            "content of {id} did not round-trip"
+        );
+    }
+}
+
+/// `why` fields that hold however the tokenizer treats these scripts: a
+/// document's `total_words` does not depend on the query, every document of
+/// one result reports the same `query_terms`, and NFC and NFD behave alike
+/// when query and document use the same form.
+#[test]
+fn unicode_why_fields_are_consistent() {
+    let runner = match cli() {
+        Some(r) => r,
+        None => {
+            eprintln!("CONTEXT_CLI_BIN not set, skipping");
+            return;
+        }
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache = build_unicode(&runner, dir.path());
+
+    let mut results = BTreeMap::new();
+    let mut total_words: BTreeMap<String, (Value, &str)> = BTreeMap::new();
+    for name in UNICODE_QUERIES {
+        let v = resolve(&runner, &cache, name);
+        let why = why_by_id(&v);
+        let mut terms = why.values().map(|w| &w["query_terms"]);
+        if let Some(first) = terms.next() {
+            assert!(terms.all(|t| t == first), "{name}: documents report different query_terms");
+        }
+        for (id, w) in &why {
+            let words = &w["total_words"];
+            let (seen, by) = total_words.entry(id.clone()).or_insert((words.clone(), name));
+            assert_eq!(words, seen, "total_words of {id} differs between {by} and {name}");
+        }
+        results.insert(*name, why);
+    }
+
+    let (nfc, nfd) = (&results["unicode_nfc"], &results["unicode_nfd"]);
+    for (on_nfc_query, on_nfd_query) in [("nfc.md", "nfd.md"), ("nfd.md", "nfc.md")] {
+        assert_eq!(
+            nfc[on_nfc_query]["term_matches"], nfd[on_nfd_query]["term_matches"],
+            "term_matches: NFC query on {on_nfc_query} vs NFD query on {on_nfd_query}"
+        );
+    }
+}